@@ -15,6 +15,20 @@ pub const USDC_ORACLE: Pubkey = pubkey!("Dpw1EAVrSB1ibxiDQyTAW6Zip3J4Btk2x4SgApQ
 pub const USDT_ORACLE: Pubkey = pubkey!("HT2PLQBcG5EiCcNSaMHAjSgd9F98ecpATbk4Sk5oYuM");
 pub const PYUSD_ORACLE: Pubkey = pubkey!("9zXQxpYH3kYhtoybmZfUNNCRVuud7fY9jswTg1hLyT8k");
 
+// mint oracle pyth feed ids
+pub const USDC_FEED_ID: [u8; 32] = [
+    0xea, 0xa0, 0x20, 0xc6, 0x1c, 0xc4, 0x79, 0x71, 0x28, 0x13, 0x46, 0x1c, 0xe1, 0x53, 0x89, 0x4a,
+    0x96, 0xa6, 0xc0, 0x0b, 0x21, 0xed, 0x0c, 0xfc, 0x27, 0x98, 0xd1, 0xf9, 0xa9, 0xe9, 0xc9, 0x4a,
+];
+pub const USDT_FEED_ID: [u8; 32] = [
+    0x2b, 0x89, 0xb9, 0xdc, 0x8f, 0xdf, 0x9f, 0x34, 0x70, 0x9a, 0x5b, 0x10, 0x6b, 0x47, 0x2f, 0x0f,
+    0x39, 0xbb, 0x6c, 0xa9, 0xce, 0x04, 0xb0, 0xfd, 0x7f, 0x2e, 0x97, 0x16, 0x88, 0xe2, 0xe5, 0x3b,
+];
+pub const PYUSD_FEED_ID: [u8; 32] = [
+    0xc1, 0xda, 0x1b, 0x73, 0xd7, 0xf0, 0x1e, 0x7d, 0xdd, 0x54, 0xb3, 0x76, 0x6c, 0xf7, 0xfc, 0xd6,
+    0x44, 0x39, 0x5a, 0xd1, 0x4f, 0x70, 0xaa, 0x70, 0x6e, 0xc5, 0x38, 0x4c, 0x59, 0xe7, 0x66, 0x92,
+];
+
 // crt vault
 pub const CRT_MINT: Pubkey = pubkey!("CRTx1JouZhzSU6XytsE42UQraoGqiHgxabocVfARTy2s");
 pub const CRT_VAULT: Pubkey = pubkey!("FfCRL34rkJiMiX5emNDrYp3MdWH2mES3FvDQyFppqgpJ");
@@ -26,6 +40,7 @@ pub const CARROT_LOG_PROGRAM: Pubkey = pubkey!("7Mc3vSdRWoThArpni6t5W4XjvQf4BuMn
 // other programs
 pub const TOKEN_PROGRAM: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 pub const TOKEN_22_PROGRAM: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const PYTH_RECEIVER_PROGRAM: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
 
 // amm label
 pub const AMM_LABEL: &str = "CarrotAmm";
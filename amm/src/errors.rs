@@ -1,6 +1,8 @@
+use solana_sdk::pubkey::Pubkey;
 use thiserror::Error;
 
-#[derive(Error, Clone, Copy, Debug)]
+#[derive(Error, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
 pub enum CarrotAmmError {
     #[error("Invalid Destination Mint")]
     InvalidDestinationMint = 0,
@@ -25,4 +27,71 @@ pub enum CarrotAmmError {
 
     #[error("Oracle Price is Stale")]
     OraclePriceStale = 7,
+
+    #[error("Vault is Paused")]
+    VaultPaused = 8,
+
+    #[error("Account Data Too Short: expected {expected} bytes, got {actual}")]
+    AccountDataTooShort { expected: usize, actual: usize } = 9,
+
+    #[error("Unknown Oracle Verification Level")]
+    UnknownVerificationLevel = 10,
+
+    #[error("Invalid Account Discriminator")]
+    InvalidAccountDiscriminator = 11,
+
+    #[error("Invalid Account Owner: {account} is owned by {owner}")]
+    InvalidAccountOwner { account: Pubkey, owner: Pubkey } = 12,
+
+    #[error("Oracle Feed Id Mismatch")]
+    OracleFeedIdMismatch = 13,
+
+    #[error("Oracle Verification Level Too Low")]
+    OracleVerificationLevelTooLow = 14,
+
+    #[error("Unknown Strategy Type")]
+    UnknownStrategyType = 15,
+
+    #[error("Insufficient Snapshots")]
+    InsufficientSnapshots = 16,
+
+    #[error("Unknown Event Discriminator")]
+    UnknownEventDiscriminator = 17,
+
+    #[error("Slippage Exceeded: expected at least {min_out_amount}, got {out_amount}")]
+    SlippageExceeded {
+        min_out_amount: u64,
+        out_amount: u64,
+    } = 18,
+
+    #[error("Invalid Slippage Bps")]
+    InvalidSlippageBps = 19,
+
+    #[error("Invalid Swap Direction")]
+    InvalidSwapDirection = 20,
+
+    #[error("Token Program Mismatch: {account} is owned by {owner}, expected {expected}")]
+    TokenProgramMismatch {
+        account: Pubkey,
+        owner: Pubkey,
+        expected: Pubkey,
+    } = 21,
+
+    #[error("Math Overflow")]
+    MathOverflow = 22,
+
+    #[error("Division By Zero")]
+    DivisionByZero = 23,
+
+    #[error("Negative Price")]
+    NegativePrice = 24,
+
+    #[error("Unknown Epoch for the Transfer Fee of {0}")]
+    UnknownEpoch(Pubkey) = 25,
+
+    #[error("Zero Deposit Amount")]
+    ZeroDepositAmount = 26,
+
+    #[error("Invalid Inner Instruction Stack Height")]
+    InvalidInstructionStack = 27,
 }
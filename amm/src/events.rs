@@ -0,0 +1,138 @@
+use anyhow::Result;
+use solana_sdk::{
+    inner_instruction::InnerInstructions, instruction::CompiledInstruction, pubkey::Pubkey,
+};
+use std::ops::RangeInclusive;
+
+use crate::{
+    constants::{CARROT_LOG_PROGRAM, CARROT_PROGRAM},
+    errors::CarrotAmmError,
+    state::{read_pubkey, read_u128, read_u64, read_u8},
+};
+
+// events the carrot program cpis into the carrot log program
+// the log program only receives them, so they are read from the inner instruction data
+// each event is a borsh enum, a one byte variant index followed by the borsh fields
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CarrotEvent {
+    Issue(IssueEvent),
+    Redeem(RedeemEvent),
+    Raw(RawEvent),
+}
+
+impl CarrotEvent {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut offset = 0;
+        let variant = read_u8(data, &mut offset)?;
+
+        let event = match variant {
+            IssueEvent::VARIANT => CarrotEvent::Issue(IssueEvent {
+                user: read_pubkey(data, &mut offset)?,
+                asset_mint: read_pubkey(data, &mut offset)?,
+                asset_amount: read_u64(data, &mut offset)?,
+                shares_amount: read_u64(data, &mut offset)?,
+                management_fee: read_u64(data, &mut offset)?,
+                tvl: read_u128(data, &mut offset)?,
+            }),
+            RedeemEvent::VARIANT => CarrotEvent::Redeem(RedeemEvent {
+                user: read_pubkey(data, &mut offset)?,
+                asset_mint: read_pubkey(data, &mut offset)?,
+                asset_amount: read_u64(data, &mut offset)?,
+                shares_amount: read_u64(data, &mut offset)?,
+                redemption_fee: read_u64(data, &mut offset)?,
+                management_fee: read_u64(data, &mut offset)?,
+                tvl: read_u128(data, &mut offset)?,
+            }),
+            variant if RawEvent::VARIANTS.contains(&variant) => CarrotEvent::Raw(RawEvent {
+                variant,
+                data: data[offset..].to_vec(),
+            }),
+            _ => return Err(CarrotAmmError::UnknownEventDiscriminator.into()),
+        };
+
+        Ok(event)
+    }
+}
+
+// shares issued for an asset deposit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IssueEvent {
+    pub user: Pubkey,
+    pub asset_mint: Pubkey,
+    pub asset_amount: u64,
+    pub shares_amount: u64,
+    // management fee shares accrued by the issue
+    pub management_fee: u64,
+    // vault tvl after the issue, rounded up
+    pub tvl: u128,
+}
+
+impl IssueEvent {
+    pub const VARIANT: u8 = 0;
+}
+
+// shares redeemed for an asset withdrawal, fees are in shares
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RedeemEvent {
+    pub user: Pubkey,
+    pub asset_mint: Pubkey,
+    pub asset_amount: u64,
+    pub shares_amount: u64,
+    pub redemption_fee: u64,
+    // management fee shares accrued by the redeem
+    pub management_fee: u64,
+    // vault tvl after the redeem, rounded down
+    pub tvl: u128,
+}
+
+impl RedeemEvent {
+    pub const VARIANT: u8 = 1;
+}
+
+// fee collection and rebalance events of the strategy and emissions instructions
+// their layout is not decoded, data holds the borsh fields after the variant index
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawEvent {
+    pub variant: u8,
+    pub data: Vec<u8>,
+}
+
+impl RawEvent {
+    pub const VARIANTS: RangeInclusive<u8> = 2..=4;
+}
+
+// decodes the events of the inner instructions where the carrot program invokes the carrot log program
+// account_keys are the transaction account keys, including loaded addresses
+// inner_instructions holds the inner instructions of each transaction instruction, in the same order
+pub fn parse_inner_instructions(
+    account_keys: &[Pubkey],
+    instructions: &[CompiledInstruction],
+    inner_instructions: &[InnerInstructions],
+) -> Result<Vec<CarrotEvent>> {
+    let program_id = |ix: &CompiledInstruction| account_keys.get(ix.program_id_index as usize);
+
+    let mut events = vec![];
+    for (ix, inner_instructions) in instructions.iter().zip(inner_instructions) {
+        // program ids of the current invocation stack, the transaction instruction is at height 1
+        let mut stack = vec![program_id(ix)];
+        for inner in inner_instructions {
+            let height = inner.stack_height as usize;
+            if height.lt(&2) || height.gt(&(stack.len() + 1)) {
+                return Err(CarrotAmmError::InvalidInstructionStack.into());
+            }
+            stack.truncate(height - 1);
+            let invoker = stack.last().copied().flatten();
+            let invoked = program_id(&inner.instruction);
+            stack.push(invoked);
+
+            // anyone can invoke the log program, only events logged by the carrot program count
+            if invoked.is_some_and(|program_id| program_id.eq(&CARROT_LOG_PROGRAM))
+                && invoker.is_some_and(|program_id| program_id.eq(&CARROT_PROGRAM))
+            {
+                events.push(CarrotEvent::decode(&inner.instruction.data)?);
+            }
+        }
+    }
+
+    Ok(events)
+}
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use crate::{constants::CARROT_PROGRAM, errors::CarrotAmmError, CarrotSwap};
+
+// sha256("global:issue")[..8]
+pub const ISSUE_DISCRIMINATOR: [u8; 8] = [0xbe, 0x01, 0x62, 0xd6, 0x51, 0x63, 0xde, 0xf7];
+
+// sha256("global:redeem")[..8]
+pub const REDEEM_DISCRIMINATOR: [u8; 8] = [0xb8, 0x0c, 0x56, 0x95, 0x46, 0xc4, 0x61, 0xe1];
+
+// deposits amount of the source asset for shares
+pub fn issue(accounts: CarrotSwap, amount: u64) -> Result<Instruction> {
+    if accounts.source_mint.eq(&accounts.shares_mint) {
+        return Err(CarrotAmmError::InvalidSwapDirection.into());
+    }
+
+    build_instruction(accounts, issue_data(amount))
+}
+
+// burns amount of shares for the destination asset
+pub fn redeem(accounts: CarrotSwap, amount: u64) -> Result<Instruction> {
+    if accounts.source_mint.ne(&accounts.shares_mint) {
+        return Err(CarrotAmmError::InvalidSwapDirection.into());
+    }
+
+    build_instruction(accounts, redeem_data(amount))
+}
+
+pub fn issue_data(amount: u64) -> Vec<u8> {
+    get_ix_data(&ISSUE_DISCRIMINATOR, amount)
+}
+
+pub fn redeem_data(amount: u64) -> Vec<u8> {
+    get_ix_data(&REDEEM_DISCRIMINATOR, amount)
+}
+
+fn build_instruction(accounts: CarrotSwap, data: Vec<u8>) -> Result<Instruction> {
+    let accounts: Vec<AccountMeta> = accounts.try_into()?;
+
+    Ok(Instruction {
+        program_id: CARROT_PROGRAM,
+        accounts,
+        data,
+    })
+}
+
+// anchor sighash followed by the borsh encoded amount
+fn get_ix_data(discriminator: &[u8; 8], amount: u64) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
@@ -1,28 +1,43 @@
-use anyhow::Result;
-use constants::{CRT_MINT, CRT_VAULT};
+use anyhow::{Context, Result};
 use jupiter_amm_interface::{
-    try_get_account_data, AccountMap, Amm, AmmContext, KeyedAccount, Quote, QuoteParams, Swap,
-    SwapAndAccountMetas, SwapParams,
+    AccountMap, Amm, AmmContext, ClockRef, KeyedAccount, Quote, QuoteParams, Swap,
+    SwapAndAccountMetas, SwapMode, SwapParams,
 };
 use rust_decimal::Decimal;
 use solana_sdk::{
-    instruction::AccountMeta, program_pack::Pack, pubkey::Pubkey,
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
     system_program::ID as SystemProgramId,
 };
 use spl_token::state::Account as TokenAccount;
 use spl_token_2022::{
-    extension::StateWithExtensionsOwned,
+    extension::{
+        interest_bearing_mint::InterestBearingConfig,
+        transfer_fee::{TransferFee, TransferFeeConfig},
+        BaseStateWithExtensions, StateWithExtensions, StateWithExtensionsOwned,
+    },
     state::{Account as TokenAccount22, Mint as Mint22},
 };
+use std::{collections::HashMap, sync::atomic::Ordering};
 
 pub mod constants;
 use constants::*;
 
-mod errors;
+pub mod errors;
+pub mod events;
+pub mod instructions;
 mod math;
 use errors::CarrotAmmError;
 use math::*;
-use state::{AssetState, PriceUpdateV2, SharesState, Vault};
+use state::{
+    check_account_owner, AssetState, FeedId, PriceSource, PriceUpdateV2, RoundingMode, SharesState,
+    Vault, VerificationLevel,
+};
+
+pub mod snapshot;
+use snapshot::VaultSnapshot;
 
 pub mod state;
 
@@ -34,6 +49,15 @@ pub struct CarrotAmm {
     pub shares_state: Option<SharesState>,
     pub asset_state: Vec<AssetState>,
     pub oracle_max_age: u64,
+    // clock shared by the router, falls back to the wall clock when not set
+    pub clock_ref: Option<ClockRef>,
+    // expected pyth feed id per asset mint, defaults to the crt vault assets
+    // other assets pin the feed of their oracle on the first update, later updates must match it
+    pub oracle_feed_ids: HashMap<Pubkey, FeedId>,
+    // minimum verification level accepted for oracle updates
+    pub oracle_min_verification_level: VerificationLevel,
+    // pyth fields used to price assets for each quote direction, applied on update
+    pub price_source: PriceSource,
 }
 
 impl CarrotAmm {
@@ -46,9 +70,36 @@ impl CarrotAmm {
             asset_state: vec![],
             shares_state: None,
             oracle_max_age,
+            clock_ref: None,
+            oracle_feed_ids: HashMap::from([
+                (USDC_MINT, USDC_FEED_ID),
+                (USDT_MINT, USDT_FEED_ID),
+                (PYUSD_MINT, PYUSD_FEED_ID),
+            ]),
+            oracle_min_verification_level: VerificationLevel::Full,
+            price_source: PriceSource::default(),
         }
     }
 
+    // unix timestamp used for fee and oracle age math
+    // prefers the on-chain clock and only uses the wall clock when no clock is available
+    pub fn current_timestamp(&self) -> i64 {
+        self.clock_ref
+            .as_ref()
+            .map(|clock_ref| clock_ref.unix_timestamp.load(Ordering::Relaxed))
+            .filter(|unix_timestamp| unix_timestamp.gt(&0))
+            .unwrap_or_else(|| chrono::Utc::now().timestamp())
+    }
+
+    // epoch of the clock shared by the router, selects the token-2022 transfer fee
+    // none when no clock is set, the wall clock cannot tell the epoch
+    fn current_epoch(&self) -> Option<u64> {
+        self.clock_ref
+            .as_ref()
+            .map(|clock_ref| clock_ref.epoch.load(Ordering::Relaxed))
+            .filter(|epoch| epoch.gt(&0))
+    }
+
     pub fn get_asset_by_mint(&self, asset_mint: &Pubkey) -> Result<&AssetState> {
         let asset_state = self
             .asset_state
@@ -63,6 +114,408 @@ impl CarrotAmm {
         let asset_state = self.get_asset_by_mint(asset_mint)?;
         Ok(asset_state.ata_amount)
     }
+
+    // builds the swap accounts for this vault, uses the asset state loaded by update
+    pub fn carrot_swap(
+        &self,
+        source_mint: Pubkey,
+        destination_mint: Pubkey,
+        user_source: Pubkey,
+        user_destination: Pubkey,
+        user_transfer_authority: Pubkey,
+    ) -> CarrotSwap {
+        CarrotSwap {
+            vault: self.vault,
+            shares_mint: self.vault_state.shares,
+            source_mint,
+            destination_mint,
+            user_source,
+            user_destination,
+            user_transfer_authority,
+            assets: self.asset_state.clone(),
+        }
+    }
+
+    // usd value of one share, priced like a redeem and net of unminted fees
+    pub fn share_price(&self) -> Result<SharePrice> {
+        let shares_state = self
+            .shares_state
+            .ok_or(CarrotAmmError::SharesStateNotInitialized)?;
+
+        let VaultNav {
+            vault_tvl,
+            adjusted_shares_supply,
+            ..
+        } = self.get_vault_nav(false)?;
+
+        let one_share = 10_u64
+            .checked_pow(shares_state.decimals.into())
+            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+        let price = usd_earned(one_share, adjusted_shares_supply, vault_tvl)?;
+        let price_decimal = i128::try_from(price)
+            .ok()
+            .and_then(|price| Decimal::try_from_i128_with_scale(price, PRECISION.into()).ok())
+            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+
+        Ok(SharePrice {
+            price,
+            price_decimal,
+            vault_tvl,
+            shares_supply: shares_state.supply,
+            adjusted_shares_supply,
+            shares_decimals: shares_state.decimals,
+        })
+    }
+
+    // point in time view of the vault nav, feed successive snapshots to snapshot::estimate_yield
+    pub fn snapshot(&self) -> Result<VaultSnapshot> {
+        let share_price = self.share_price()?;
+        let slot = self
+            .clock_ref
+            .as_ref()
+            .map(|clock_ref| clock_ref.slot.load(Ordering::Relaxed))
+            .unwrap_or_default();
+
+        Ok(VaultSnapshot {
+            slot,
+            timestamp: self.current_timestamp(),
+            vault_tvl: share_price.vault_tvl,
+            adjusted_shares_supply: share_price.adjusted_shares_supply,
+            nav: share_price.price,
+        })
+    }
+
+    // quote with every intermediate value of the pricing, quote returns the same amounts
+    pub fn quote_detailed(&self, quote_params: &QuoteParams) -> Result<QuoteBreakdown> {
+        // the program rejects issue and redeem while the vault is paused
+        if self.vault_state.paused {
+            return Err(CarrotAmmError::VaultPaused.into());
+        }
+
+        // an empty vault would quote the bootstrap shares for a zero deposit
+        let is_issue = quote_params.output_mint.eq(&self.vault_state.shares);
+        if is_issue && quote_params.amount.eq(&0) {
+            return Err(CarrotAmmError::ZeroDepositAmount.into());
+        }
+
+        match quote_params.swap_mode {
+            SwapMode::ExactIn => self.quote_exact_in(
+                &quote_params.input_mint,
+                &quote_params.output_mint,
+                quote_params.amount,
+            ),
+            SwapMode::ExactOut => self.quote_exact_out(
+                &quote_params.input_mint,
+                &quote_params.output_mint,
+                quote_params.amount,
+            ),
+        }
+    }
+
+    // issue or redeem instruction for a quote along with the minimum output accepted
+    // the program takes no minimum output, so callers check the destination balance
+    // change, e.g. from a simulation, with SlippageSwap::check before sending
+    pub fn swap_with_slippage(
+        &self,
+        quote_params: &QuoteParams,
+        slippage_bps: u16,
+        user_source: Pubkey,
+        user_destination: Pubkey,
+        user_transfer_authority: Pubkey,
+    ) -> Result<SlippageSwap> {
+        if slippage_bps.gt(&10_000) {
+            return Err(CarrotAmmError::InvalidSlippageBps.into());
+        }
+
+        let quote = self.quote(quote_params)?;
+
+        // rounds down so the minimum never exceeds what the slippage allows
+        let min_out_amount = (quote.out_amount as u128)
+            .checked_mul((10_000 - slippage_bps).into())
+            .and_then(|prod| prod.checked_div(10_000))
+            .and_then(|min_out| u64::try_from(min_out).ok())
+            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+
+        let carrot_swap = self.carrot_swap(
+            quote_params.input_mint,
+            quote_params.output_mint,
+            user_source,
+            user_destination,
+            user_transfer_authority,
+        );
+
+        let instruction = if quote_params.input_mint.eq(&self.vault_state.shares) {
+            instructions::redeem(carrot_swap, quote.in_amount)?
+        } else {
+            instructions::issue(carrot_swap, quote.in_amount)?
+        };
+
+        Ok(SlippageSwap {
+            instruction,
+            in_amount: quote.in_amount,
+            out_amount: quote.out_amount,
+            min_out_amount,
+            user_destination,
+        })
+    }
+
+    // tvl and shares supply used to price shares against the vault
+    fn get_vault_nav(&self, round_up: bool) -> Result<VaultNav> {
+        let vault_tvl = self.vault_state.get_tvl(&self.asset_state, round_up)?;
+
+        let shares_state = self
+            .shares_state
+            .ok_or(CarrotAmmError::SharesStateNotInitialized)?;
+
+        // calculate unminted performance fees, used to adjust the shares supply
+        let accumulated_performance_fee = self.vault_state.calculate_accumulated_performance_fee(
+            &self.asset_state,
+            shares_state.supply,
+            shares_state.decimals,
+            vault_tvl,
+            round_up,
+        )?;
+
+        // adjust shares supply by unminted fees accrued
+        // this is just used to have an accurate supply to calculate the management fee
+        let adjusted_shares_supply_before_mgmt_fee = self
+            .vault_state
+            .fee
+            .adjust_shares_by_fees(shares_state.supply, accumulated_performance_fee)?;
+
+        // calculate management fee before deposit
+        let fee_amount = self.vault_state.fee.calculate_management_fee(
+            vault_tvl,
+            adjusted_shares_supply_before_mgmt_fee,
+            shares_state.decimals,
+            self.current_timestamp(),
+        )?;
+
+        // adjust shares supply by unminted fees accrued
+        // this is now the true adjusted shares supply because it takes into account the latest fee data
+        let adjusted_shares_supply = self.vault_state.fee.adjust_shares_by_fees(
+            shares_state
+                .supply
+                .checked_add(fee_amount)
+                .ok_or(CarrotAmmError::InvalidFeeCalculation)?,
+            accumulated_performance_fee,
+        )?;
+
+        Ok(VaultNav {
+            vault_tvl,
+            shares_supply: shares_state.supply,
+            performance_fee_shares: accumulated_performance_fee,
+            management_fee_shares: fee_amount,
+            accumulated_fee_shares: self
+                .vault_state
+                .fee
+                .management_fee_accumulated
+                .checked_add(self.vault_state.fee.redemption_fee_accumulated)
+                .ok_or(CarrotAmmError::InvalidFeeCalculation)?,
+            adjusted_shares_supply,
+        })
+    }
+
+    fn quote_exact_in(
+        &self,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<QuoteBreakdown> {
+        let is_redeem = input_mint.eq(&self.vault_state.shares);
+        let round_up = !is_redeem;
+        let nav = self.get_vault_nav(round_up)?;
+        let VaultNav {
+            vault_tvl,
+            adjusted_shares_supply,
+            ..
+        } = nav;
+
+        let (out_amount, asset, usd_value, fee_pct, fee_amount, transfer_fee_amount) = if is_redeem
+        {
+            // calculate redemption fee
+            let (fee_adjusted_input_amount, redemption_fee_amount) =
+                self.vault_state.fee.calculate_redemption_fee(amount)?;
+
+            let redeem_amount_usd =
+                usd_earned(fee_adjusted_input_amount, adjusted_shares_supply, vault_tvl)?;
+
+            let asset = self.get_asset_by_mint(output_mint)?;
+
+            let asset_amount = calc_token_amount(
+                redeem_amount_usd,
+                asset.mint_decimals,
+                asset.get_price(round_up),
+                asset.oracle_price_expo,
+                false,
+            )?;
+
+            // check that we have sufficient liquidity for redemption
+            let asset_liquidity = self.get_asset_liquidity(output_mint)?;
+            if asset_amount.gt(&asset_liquidity) {
+                return Err(CarrotAmmError::InsufficientLiquidity.into());
+            }
+
+            // the user receives the asset net of its transfer fee
+            let received_amount = asset.amount_after_transfer_fee(asset_amount)?;
+
+            (
+                received_amount,
+                asset,
+                redeem_amount_usd,
+                Decimal::new(self.vault_state.fee.redemption_fee_bps.into(), 4),
+                redemption_fee_amount,
+                asset_amount - received_amount,
+            )
+        } else {
+            // if input is not shares, its an issue operation
+            let asset = self.get_asset_by_mint(input_mint)?;
+            let shares_decimals = self
+                .shares_state
+                .ok_or(CarrotAmmError::SharesStateNotInitialized)?
+                .decimals;
+
+            // the vault receives the deposit net of its transfer fee,
+            // but the program prices the shares on the full amount
+            let received_amount = asset.amount_after_transfer_fee(amount)?;
+
+            let deposit_usd = calc_usd_amount(
+                amount,
+                asset.mint_decimals,
+                asset.get_price(round_up),
+                asset.oracle_price_expo,
+                false,
+            )?;
+
+            // determine shares owed to depositor
+            let shares_owed = shares_earned(
+                deposit_usd,
+                adjusted_shares_supply,
+                shares_decimals,
+                vault_tvl,
+                false,
+            )?;
+
+            (
+                shares_owed,
+                asset,
+                deposit_usd,
+                Decimal::ZERO,
+                0,
+                amount - received_amount,
+            )
+        };
+
+        Ok(QuoteBreakdown {
+            input_mint: *input_mint,
+            output_mint: *output_mint,
+            is_redeem,
+            in_amount: amount,
+            out_amount,
+            nav,
+            asset_mint: asset.mint,
+            asset_price: asset.get_price(round_up),
+            asset_price_expo: asset.oracle_price_expo,
+            usd_value,
+            fee_pct,
+            fee_amount,
+            transfer_fee_amount,
+        })
+    }
+
+    // inverts every rounding step of quote_exact_in so the returned in_amount is the
+    // smallest amount that yields at least `amount` out, then re-quotes it exact in
+    fn quote_exact_out(
+        &self,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<QuoteBreakdown> {
+        let is_redeem = input_mint.eq(&self.vault_state.shares);
+        let round_up = !is_redeem;
+        let VaultNav {
+            vault_tvl,
+            adjusted_shares_supply,
+            ..
+        } = self.get_vault_nav(round_up)?;
+
+        let in_amount = if is_redeem {
+            let asset = self.get_asset_by_mint(output_mint)?;
+
+            // asset amount the vault sends so the user receives the requested amount
+            let asset_amount = asset.amount_before_transfer_fee(amount)?;
+
+            // minimum usd value that converts to that asset amount
+            let redeem_amount_usd = calc_token_amount_inverse(
+                asset_amount,
+                asset.mint_decimals,
+                asset.get_price(round_up),
+                asset.oracle_price_expo,
+            )?;
+
+            // minimum shares left after the redemption fee to earn that usd value
+            let fee_adjusted_input_amount =
+                usd_earned_inverse(redeem_amount_usd, adjusted_shares_supply, vault_tvl)?;
+
+            // gross up by the redemption fee
+            let (redemption_amount, _) = self
+                .vault_state
+                .fee
+                .calculate_redemption_fee_inverse(fee_adjusted_input_amount)?;
+
+            redemption_amount
+        } else {
+            let asset = self.get_asset_by_mint(input_mint)?;
+
+            // minimum usd value that earns the requested shares
+            let deposit_usd = shares_earned_inverse(amount, adjusted_shares_supply, vault_tvl)?;
+
+            // minimum asset amount worth that usd value, the transfer fee does not change the shares
+            calc_usd_amount_inverse(
+                deposit_usd,
+                asset.mint_decimals,
+                asset.get_price(round_up),
+                asset.oracle_price_expo,
+            )?
+        };
+
+        let quote = self.quote_exact_in(input_mint, output_mint, in_amount)?;
+
+        // never return a quote that under-delivers the requested amount
+        if quote.out_amount.lt(&amount) {
+            return Err(CarrotAmmError::InvalidTokenCalculation.into());
+        }
+
+        Ok(quote)
+    }
+}
+
+// account from the map, data and owner are both needed to validate it
+fn try_get_account<'a>(account_map: &'a AccountMap, address: &Pubkey) -> Result<&'a Account> {
+    account_map
+        .get(address)
+        .with_context(|| format!("Could not find address: {address}"))
+}
+
+// transfer fee of the mint for the epoch
+// without an epoch the fee is only known when the older and newer fees are the same
+fn get_transfer_fee(
+    config: &TransferFeeConfig,
+    epoch: Option<u64>,
+    mint: &Pubkey,
+) -> Result<TransferFee> {
+    let (older, newer) = (config.older_transfer_fee, config.newer_transfer_fee);
+    match epoch {
+        Some(epoch) => Ok(*config.get_epoch_fee(epoch)),
+        None if older
+            .transfer_fee_basis_points
+            .eq(&newer.transfer_fee_basis_points)
+            && older.maximum_fee.eq(&newer.maximum_fee) =>
+        {
+            Ok(newer)
+        }
+        None => Err(CarrotAmmError::UnknownEpoch(*mint).into()),
+    }
 }
 
 impl Clone for CarrotAmm {
@@ -75,108 +528,199 @@ impl Clone for CarrotAmm {
             asset_state: self.asset_state.clone(),
             shares_state: self.shares_state,
             oracle_max_age: self.oracle_max_age,
+            clock_ref: self.clock_ref.clone(),
+            oracle_feed_ids: self.oracle_feed_ids.clone(),
+            oracle_min_verification_level: self.oracle_min_verification_level,
+            price_source: self.price_source,
+        }
+    }
+}
+
+// vault tvl and shares supply adjusted by fees that are accrued but not minted yet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VaultNav {
+    // usd with 9 decimals
+    pub vault_tvl: u128,
+    // supply of the shares mint
+    pub shares_supply: u64,
+    // unminted performance fee shares
+    pub performance_fee_shares: u64,
+    // management fee shares accrued since the last update
+    pub management_fee_shares: u64,
+    // management and redemption fee shares already accumulated in the vault
+    pub accumulated_fee_shares: u64,
+    pub adjusted_shares_supply: u64,
+}
+
+// every intermediate value behind a quote
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuoteBreakdown {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub is_redeem: bool,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    // nav priced with round up for issue and round down for redeem
+    pub nav: VaultNav,
+    // vault asset deposited or withdrawn, and the oracle price used for it
+    pub asset_mint: Pubkey,
+    pub asset_price: i64,
+    pub asset_price_expo: i32,
+    // usd with 9 decimals deposited on issue, or earned by the shares left after the fee on redeem
+    pub usd_value: u128,
+    // redemption fee, paid in shares
+    pub fee_pct: Decimal,
+    pub fee_amount: u64,
+    // token-2022 transfer fee withheld from the asset transfer
+    pub transfer_fee_amount: u64,
+}
+
+impl QuoteBreakdown {
+    pub fn quote(&self) -> Quote {
+        Quote {
+            fee_pct: self.fee_pct,
+            in_amount: self.in_amount,
+            out_amount: self.out_amount,
+            fee_amount: self.fee_amount,
+            fee_mint: self.input_mint,
+            ..Quote::default()
+        }
+    }
+}
+
+// nav per share, usd amounts have 9 decimals like the vault tvl
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SharePrice {
+    pub price: u128,
+    pub price_decimal: Decimal,
+    pub vault_tvl: u128,
+    // supply of the shares mint
+    pub shares_supply: u64,
+    // supply including unminted performance and management fees
+    pub adjusted_shares_supply: u64,
+    pub shares_decimals: u8,
+}
+
+// carrot swap instruction with the minimum output accepted by the caller
+#[derive(Clone, Debug)]
+pub struct SlippageSwap {
+    pub instruction: Instruction,
+    pub in_amount: u64,
+    // quoted output
+    pub out_amount: u64,
+    pub min_out_amount: u64,
+    // token account that receives the output
+    pub user_destination: Pubkey,
+}
+
+impl SlippageSwap {
+    // errors if the destination balance grew by less than the minimum output
+    pub fn check(
+        &self,
+        destination_balance_before: u64,
+        destination_balance_after: u64,
+    ) -> Result<()> {
+        let out_amount = destination_balance_after.saturating_sub(destination_balance_before);
+        if out_amount.lt(&self.min_out_amount) {
+            return Err(CarrotAmmError::SlippageExceeded {
+                min_out_amount: self.min_out_amount,
+                out_amount,
+            }
+            .into());
         }
+
+        Ok(())
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct CarrotSwap {
+    pub vault: Pubkey,
+    pub shares_mint: Pubkey,
     pub source_mint: Pubkey,
     pub destination_mint: Pubkey,
     pub user_source: Pubkey,
     pub user_destination: Pubkey,
     pub user_transfer_authority: Pubkey,
+    // vault assets as loaded by CarrotAmm::update, used to derive the asset accounts
+    pub assets: Vec<AssetState>,
 }
 
 impl TryFrom<CarrotSwap> for Vec<AccountMeta> {
     type Error = anyhow::Error;
 
     fn try_from(accounts: CarrotSwap) -> Result<Self> {
-        let (
-            user_shares_token_account,
-            user_asset_token_account,
-            asset_mint,
-            vault_ata,
-            token_program,
-        ) = if accounts.source_mint.eq(&CRT_MINT) {
-            // redeem operation
-
-            // determine the vault ata according to the destination mint requested by the user
-            let (vault_ata, token_program) = match accounts.destination_mint {
-                USDC_MINT => (USDC_VAULT_ATA, TOKEN_PROGRAM),
-                USDT_MINT => (USDT_VAULT_ATA, TOKEN_PROGRAM),
-                PYUSD_MINT => (PYUSD_VAULT_ATA, TOKEN_22_PROGRAM),
-                _ => return Err(CarrotAmmError::InvalidDestinationMint.into()),
-            };
+        let find_asset = |mint: &Pubkey| accounts.assets.iter().find(|a| a.mint.eq(mint));
 
-            // source is expected to be shares since thats the input
-            // destination is expected to be the asset since thats the output
-            (
-                accounts.user_source,
-                accounts.user_destination,
-                accounts.destination_mint,
-                vault_ata,
-                token_program,
-            )
-        } else {
-            // issue operation
-
-            // determine the vault ata according to the destination mint requested by the user
-            let (vault_ata, token_program) = match accounts.source_mint {
-                USDC_MINT => (USDC_VAULT_ATA, TOKEN_PROGRAM),
-                USDT_MINT => (USDT_VAULT_ATA, TOKEN_PROGRAM),
-                PYUSD_MINT => (PYUSD_VAULT_ATA, TOKEN_22_PROGRAM),
-                _ => return Err(CarrotAmmError::InvalidSourceMint.into()),
-            };
+        let (user_shares_token_account, user_asset_token_account, asset) =
+            if accounts.source_mint.eq(&accounts.shares_mint) {
+                // redeem operation
 
-            // source is expected to be asset since thats the input
-            // destination is expected to be the shares since thats the output
-            (
-                accounts.user_destination,
-                accounts.user_source,
-                accounts.source_mint,
-                vault_ata,
-                token_program,
-            )
-        };
+                // determine the asset according to the destination mint requested by the user
+                let asset = find_asset(&accounts.destination_mint)
+                    .ok_or(CarrotAmmError::InvalidDestinationMint)?;
+
+                // source is expected to be shares since thats the input
+                // destination is expected to be the asset since thats the output
+                (accounts.user_source, accounts.user_destination, asset)
+            } else {
+                // issue operation
+
+                // determine the asset according to the source mint provided by the user
+                let asset =
+                    find_asset(&accounts.source_mint).ok_or(CarrotAmmError::InvalidSourceMint)?;
+
+                // source is expected to be asset since thats the input
+                // destination is expected to be the shares since thats the output
+                (accounts.user_destination, accounts.user_source, asset)
+            };
 
         let mut account_metas = vec![
-            AccountMeta::new(CRT_VAULT, false),
-            AccountMeta::new(CRT_MINT, false),
+            AccountMeta::new(accounts.vault, false),
+            AccountMeta::new(accounts.shares_mint, false),
             AccountMeta::new(user_shares_token_account, false),
-            AccountMeta::new_readonly(asset_mint, false),
-            AccountMeta::new(vault_ata, false),
+            AccountMeta::new_readonly(asset.mint, false),
+            AccountMeta::new(asset.ata, false),
             AccountMeta::new(user_asset_token_account, false),
             AccountMeta::new_readonly(accounts.user_transfer_authority, true),
             AccountMeta::new_readonly(SystemProgramId, false),
-            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(asset.mint_token_program, false),
             AccountMeta::new_readonly(TOKEN_22_PROGRAM, false),
             AccountMeta::new_readonly(CARROT_LOG_PROGRAM, false),
         ];
 
         // Add remaining accounts depending on assets the vault holds
-        account_metas.extend_from_slice(&[
-            AccountMeta::new_readonly(USDC_ORACLE, false),
-            AccountMeta::new_readonly(USDT_ORACLE, false),
-            AccountMeta::new_readonly(PYUSD_ORACLE, false),
-            AccountMeta::new_readonly(USDC_VAULT_ATA, false),
-            AccountMeta::new_readonly(USDT_VAULT_ATA, false),
-            AccountMeta::new_readonly(PYUSD_VAULT_ATA, false),
-        ]);
+        // all oracles followed by all vault atas, in vault asset order
+        account_metas.extend(
+            accounts
+                .assets
+                .iter()
+                .map(|a| AccountMeta::new_readonly(a.oracle, false)),
+        );
+        account_metas.extend(
+            accounts
+                .assets
+                .iter()
+                .map(|a| AccountMeta::new_readonly(a.ata, false)),
+        );
 
         Ok(account_metas)
     }
 }
 
 impl Amm for CarrotAmm {
-    fn from_keyed_account(keyed_account: &KeyedAccount, _amm_context: &AmmContext) -> Result<Self> {
+    fn from_keyed_account(keyed_account: &KeyedAccount, amm_context: &AmmContext) -> Result<Self> {
+        check_account_owner(
+            &keyed_account.key,
+            &keyed_account.account.owner,
+            &[CARROT_PROGRAM],
+        )?;
         let vault_state = Vault::load(&keyed_account.account.data)?;
 
-        Ok(CarrotAmm::new(
-            keyed_account.key,
-            vault_state,
-            state::MAX_AGE,
-        ))
+        let mut carrot_amm = CarrotAmm::new(keyed_account.key, vault_state, state::MAX_AGE);
+        carrot_amm.clock_ref = Some(amm_context.clock_ref.clone());
+
+        Ok(carrot_amm)
     }
 
     fn label(&self) -> String {
@@ -192,7 +736,10 @@ impl Amm for CarrotAmm {
     }
 
     fn get_reserve_mints(&self) -> Vec<Pubkey> {
-        vec![CRT_MINT, USDC_MINT, USDT_MINT, PYUSD_MINT]
+        // shares mint followed by every asset the vault accepts
+        let mut mints = vec![self.vault_state.shares];
+        mints.extend(self.vault_state.assets.iter().map(|a| a.mint));
+        mints
     }
 
     fn get_accounts_to_update(&self) -> Vec<Pubkey> {
@@ -201,7 +748,7 @@ impl Amm for CarrotAmm {
 
         // add all assets
         for a in self.vault_state.assets.iter() {
-            accounts.extend(vec![a.ata, a.oracle]);
+            accounts.extend(vec![a.mint, a.ata, a.oracle]);
         }
 
         accounts
@@ -209,13 +756,19 @@ impl Amm for CarrotAmm {
 
     fn update(&mut self, account_map: &AccountMap) -> Result<()> {
         // update vault state
-        let vault_data = try_get_account_data(account_map, &self.vault)?;
-        let vault = Vault::load(vault_data)?;
+        let vault_account = try_get_account(account_map, &self.vault)?;
+        check_account_owner(&self.vault, &vault_account.owner, &[CARROT_PROGRAM])?;
+        let vault = Vault::load(&vault_account.data)?;
         self.vault_state = vault;
 
         // update shares state
-        let mint_data = try_get_account_data(account_map, &self.vault_state.shares)?;
-        let mint = StateWithExtensionsOwned::<Mint22>::unpack(mint_data.to_vec())?;
+        let mint_account = try_get_account(account_map, &self.vault_state.shares)?;
+        check_account_owner(
+            &self.vault_state.shares,
+            &mint_account.owner,
+            &[TOKEN_PROGRAM, TOKEN_22_PROGRAM],
+        )?;
+        let mint = StateWithExtensionsOwned::<Mint22>::unpack(mint_account.data.clone())?;
         self.shares_state = Some(SharesState {
             mint: self.vault_state.shares,
             supply: mint.base.supply,
@@ -223,35 +776,103 @@ impl Amm for CarrotAmm {
         });
 
         // update state for vault assets
+        let current_time = self.current_timestamp();
+        let current_epoch = self.current_epoch();
         let mut asset_state: Vec<AssetState> = Vec::with_capacity(self.vault_state.assets.len());
         for asset in self.vault_state.assets.iter() {
-            let ata_data = try_get_account_data(account_map, &asset.ata)?;
-
-            // try to parse first as regular spl-token and if that errors try spl-token-2022
-            let ata_amount = match TokenAccount::unpack(ata_data) {
-                Ok(ata) => ata.amount,
-                Err(_) => {
-                    let ata =
-                        StateWithExtensionsOwned::<TokenAccount22>::unpack(ata_data.to_vec())?;
-                    ata.base.amount
+            let Account {
+                data: ata_data,
+                owner: ata_owner,
+                ..
+            } = try_get_account(account_map, &asset.ata)?;
+            check_account_owner(&asset.ata, ata_owner, &[TOKEN_PROGRAM, TOKEN_22_PROGRAM])?;
+
+            // the vault ata must be owned by the same token program as its mint
+            let Account {
+                data: asset_mint_data,
+                owner: asset_mint_owner,
+                ..
+            } = try_get_account(account_map, &asset.mint)?;
+            check_account_owner(
+                &asset.mint,
+                asset_mint_owner,
+                &[TOKEN_PROGRAM, TOKEN_22_PROGRAM],
+            )?;
+            if ata_owner.ne(asset_mint_owner) {
+                return Err(CarrotAmmError::TokenProgramMismatch {
+                    account: asset.ata,
+                    owner: *ata_owner,
+                    expected: *asset_mint_owner,
                 }
+                .into());
+            }
+
+            // parse the ata with the program that owns it
+            let ata_amount = if ata_owner.eq(&TOKEN_PROGRAM) {
+                TokenAccount::unpack(ata_data)?.amount
+            } else {
+                StateWithExtensions::<TokenAccount22>::unpack(ata_data)?
+                    .base
+                    .amount
             };
 
-            // parse pyth oracle data
-            let oracle_data = try_get_account_data(account_map, &asset.oracle)?;
-            let oracle = PriceUpdateV2::load(oracle_data)?;
+            // parse mint extensions that change the amounts transferred
+            let asset_mint = StateWithExtensions::<Mint22>::unpack(asset_mint_data)?;
+            let transfer_fee = asset_mint
+                .get_extension::<TransferFeeConfig>()
+                .ok()
+                .map(|config| get_transfer_fee(config, current_epoch, &asset.mint))
+                .transpose()?;
+            let interest_bearing = asset_mint
+                .get_extension::<InterestBearingConfig>()
+                .ok()
+                .copied();
 
-            // get price adjusted by confidence interval
-            let (price, expo) = oracle
-                .get_price_usd_from_pyth_oracle(self.oracle_max_age, state::RoundingMode::Avg)?;
+            // parse pyth oracle data
+            let oracle_account = try_get_account(account_map, &asset.oracle)?;
+            check_account_owner(
+                &asset.oracle,
+                &oracle_account.owner,
+                &[PYTH_RECEIVER_PROGRAM],
+            )?;
+            let oracle = PriceUpdateV2::load(&oracle_account.data)?;
+            // assets without a known feed pin the feed of the vault's oracle on first sight
+            let expected_feed_id = self
+                .oracle_feed_ids
+                .entry(asset.mint)
+                .or_insert(oracle.price_message.feed_id);
+            oracle.check_price_feed(expected_feed_id, self.oracle_min_verification_level)?;
+
+            // cache prices for each quote direction, errors if the oracle is stale
+            // issue rounds up and redeem rounds down
+            let (price_up, expo) = oracle.get_price_usd_from_pyth_oracle(
+                self.oracle_max_age,
+                self.price_source,
+                RoundingMode::RoundUp,
+                current_time,
+            )?;
+            let (price_down, _) = oracle.get_price_usd_from_pyth_oracle(
+                self.oracle_max_age,
+                self.price_source,
+                RoundingMode::RoundDown,
+                current_time,
+            )?;
 
             asset_state.push(AssetState {
                 asset_id: asset.asset_id,
                 mint: asset.mint,
                 mint_decimals: asset.decimals,
+                mint_token_program: *asset_mint_owner,
+                ata_token_program: *ata_owner,
+                ata: asset.ata,
                 ata_amount,
-                oracle_price: price,
+                oracle: asset.oracle,
+                oracle_price_up: price_up,
+                oracle_price_down: price_down,
                 oracle_price_expo: expo,
+                oracle_price_feed: oracle.price_message,
+                transfer_fee,
+                interest_bearing,
             });
         }
         self.asset_state = asset_state;
@@ -260,113 +881,7 @@ impl Amm for CarrotAmm {
     }
 
     fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
-        let is_redeem = quote_params.input_mint.eq(&self.vault_state.shares);
-        let round_up = !is_redeem;
-        let vault_tvl = self.vault_state.get_tvl(&self.asset_state, round_up)?;
-
-        let shares_state = self
-            .shares_state
-            .ok_or(CarrotAmmError::SharesStateNotInitialized)?;
-
-        // calculate unminted performance fees, used to adjust the shares supply
-        let accumulated_performance_fee = self.vault_state.calculate_accumulated_performance_fee(
-            &self.asset_state,
-            shares_state.supply,
-            shares_state.decimals,
-            vault_tvl,
-        )?;
-
-        // adjust shares supply by unminted fees accrued
-        // this is just used to have an accurate supply to calculate the management fee
-        let adjusted_shares_supply_before_mgmt_fee = self
-            .vault_state
-            .fee
-            .adjust_shares_by_fees(shares_state.supply, accumulated_performance_fee)?;
-
-        // calculate management fee before deposit
-        let fee_amount = self.vault_state.fee.calculate_management_fee(
-            vault_tvl,
-            adjusted_shares_supply_before_mgmt_fee,
-            shares_state.decimals,
-        )?;
-
-        // adjust shares supply by unminted fees accrued
-        // this is now the true adjusted shares supply because it takes into account the latest fee data
-        let adjusted_shares_supply = self.vault_state.fee.adjust_shares_by_fees(
-            shares_state
-                .supply
-                .checked_add(fee_amount)
-                .ok_or(CarrotAmmError::InvalidFeeCalculation)?,
-            accumulated_performance_fee,
-        )?;
-
-        let (out_amount, fee_pct, fee_amount) = if is_redeem {
-            // calculate redemption fee
-            let (fee_adjusted_input_amount, redemption_fee_amount) = self
-                .vault_state
-                .fee
-                .calculate_redemption_fee(quote_params.amount)?;
-
-            let redeem_amount_usd =
-                usd_earned(fee_adjusted_input_amount, adjusted_shares_supply, vault_tvl)
-                    .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
-
-            let asset = self.get_asset_by_mint(&quote_params.output_mint)?;
-
-            let asset_amount = calc_token_amount(
-                redeem_amount_usd,
-                asset.mint_decimals,
-                asset.oracle_price,
-                asset.oracle_price_expo,
-                false,
-            )
-            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
-
-            // check that we have sufficient liquidity for redemption
-            let asset_liquidity = self.get_asset_liquidity(&quote_params.output_mint)?;
-            if asset_amount.gt(&asset_liquidity) {
-                return Err(CarrotAmmError::InsufficientLiquidity.into());
-            }
-
-            (
-                asset_amount,
-                Decimal::new(self.vault_state.fee.redemption_fee_bps.into(), 4),
-                redemption_fee_amount,
-            )
-        } else {
-            // if input is not shares, its an issue operation
-            let asset = self.get_asset_by_mint(&quote_params.input_mint)?;
-
-            let deposit_usd = calc_usd_amount(
-                quote_params.amount,
-                asset.mint_decimals,
-                asset.oracle_price,
-                asset.oracle_price_expo,
-                false,
-            )
-            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
-
-            // determine shares owed to depositor
-            let shares_owed = shares_earned(
-                deposit_usd,
-                adjusted_shares_supply,
-                shares_state.decimals,
-                vault_tvl,
-                false,
-            )
-            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
-
-            (shares_owed, Decimal::ZERO, 0)
-        };
-
-        Ok(Quote {
-            fee_pct,
-            in_amount: quote_params.amount,
-            out_amount,
-            fee_amount,
-            fee_mint: quote_params.input_mint,
-            ..Quote::default()
-        })
+        Ok(self.quote_detailed(quote_params)?.quote())
     }
 
     fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas> {
@@ -379,16 +894,21 @@ impl Amm for CarrotAmm {
             ..
         } = swap_params;
 
+        if self.vault_state.paused {
+            return Err(CarrotAmmError::VaultPaused.into());
+        }
+
         Ok(SwapAndAccountMetas {
             swap: Swap::TokenSwap,
-            account_metas: CarrotSwap {
-                source_mint: *source_mint,
-                destination_mint: *destination_mint,
-                user_source: *source_token_account,
-                user_destination: *destination_token_account,
-                user_transfer_authority: *token_transfer_authority,
-            }
-            .try_into()?,
+            account_metas: self
+                .carrot_swap(
+                    *source_mint,
+                    *destination_mint,
+                    *source_token_account,
+                    *destination_token_account,
+                    *token_transfer_authority,
+                )
+                .try_into()?,
         })
     }
 
@@ -397,6 +917,11 @@ impl Amm for CarrotAmm {
     }
 
     fn supports_exact_out(&self) -> bool {
-        false
+        true
+    }
+
+    // lets routers drop the market entirely while the vault is paused
+    fn is_active(&self) -> bool {
+        !self.vault_state.paused
     }
 }
@@ -1,3 +1,6 @@
+use crate::errors::CarrotAmmError;
+use anyhow::Result;
+
 // calculate the shares earned from depositing the usd
 pub fn shares_earned(
     usd_value: u128,
@@ -5,42 +8,43 @@ pub fn shares_earned(
     shares_decimals: u8,
     vault_tvl: u128,
     round_up: bool,
-) -> Option<u64> {
+) -> Result<u64> {
     if vault_tvl.le(&0) || shares_supply.le(&0) {
-        // if vault_tvl or shares_supply is 0 or less, just mint 100 shares
-        ui_to_amount(100.0, shares_decimals)
+        // if vault_tvl or shares_supply is 0, the deposit bootstraps the vault
+        return bootstrap_shares(shares_decimals);
+    }
+
+    let prod = checked_mul(usd_value, shares_supply as u128)?;
+    let shares = if round_up {
+        checked_ceil_div(prod, vault_tvl)?
     } else {
-        // Calculate shares
-        let shares = if round_up {
-            let prod = usd_value.checked_mul(shares_supply as u128)?;
-            let sum = prod.checked_add(vault_tvl - 1)?;
-            sum.checked_div(vault_tvl)?
-        } else {
-            let prod = usd_value.checked_mul(shares_supply as u128)?;
-            prod.checked_div(vault_tvl)?
-        };
+        checked_div(prod, vault_tvl)?
+    };
 
-        // Check if the result fits within u64
-        if shares > u64::MAX as u128 {
-            Some(u64::MAX) // Handle overflow case, e.g., by returning the maximum u64 value
-        } else {
-            Some(shares as u64)
-        }
-    }
+    to_u64(shares)
 }
 
-pub fn usd_earned(shares_to_redeem: u64, shares_supply: u64, vault_tvl: u128) -> Option<u128> {
-    if vault_tvl.le(&0) || shares_supply.le(&0) {
-        // if vault_tvl or shares_supply is 0 or less, return 0 USD
-        Some(0)
-    } else {
-        // rounds down
-        let usd_earned: u128 = (shares_to_redeem as u128)
-            .checked_mul(vault_tvl)?
-            .checked_div(shares_supply as u128)?;
+// shares minted by a deposit into a vault with no tvl or no shares
+// read from the program's empty vault branch, a flat 100 ui shares whatever the deposit size
+// exact up to 17 decimals, tests/empty_vault.rs checks the amount against carrot.so
+pub fn bootstrap_shares(shares_decimals: u8) -> Result<u64> {
+    to_u64(checked_mul(
+        pow10(shares_decimals.into())?,
+        BOOTSTRAP_SHARES_UI,
+    )?)
+}
 
-        Some(usd_earned)
+pub fn usd_earned(shares_to_redeem: u64, shares_supply: u64, vault_tvl: u128) -> Result<u128> {
+    if vault_tvl.le(&0) || shares_supply.le(&0) {
+        // if vault_tvl or shares_supply is 0, return 0 USD
+        return Ok(0);
     }
+
+    // rounds down
+    checked_div(
+        checked_mul(shares_to_redeem as u128, vault_tvl)?,
+        shares_supply as u128,
+    )
 }
 
 pub fn calc_usd_amount(
@@ -49,86 +53,183 @@ pub fn calc_usd_amount(
     price_feed_price: i64,
     price_feed_expo: i32,
     ceiling: bool,
-) -> Option<u128> {
-    if price_feed_expo >= 0 {
-        return None;
-    }
-
-    let token_amount = token_amount as u128;
-    let price_feed_price = price_feed_price.abs() as u128;
-
-    // Scale the token amount to the base unit (USD cents)
-    let scaled_token_amount =
-        token_amount.checked_mul(10_u128.pow((PRECISION - token_decimal) as u32))?;
-
-    // Perform safe multiplication to get numerator
-    let numerator = scaled_token_amount.checked_mul(price_feed_price)?;
+) -> Result<u128> {
+    to_usd(
+        token_amount as u128,
+        unsigned_price(price_feed_price)?,
+        usd_exponent(token_decimal, price_feed_expo),
+        ceiling,
+    )
+}
 
-    let result = {
-        let divisor = 10_u128.checked_pow((-price_feed_expo) as u32)?;
+pub fn calc_token_amount(
+    scaled_usd_amount: u128,
+    token_decimal: u8,
+    price_feed_price: i64,
+    price_feed_expo: i32,
+    ceiling: bool,
+) -> Result<u64> {
+    to_u64(to_tokens(
+        scaled_usd_amount,
+        unsigned_price(price_feed_price)?,
+        usd_exponent(token_decimal, price_feed_expo),
+        ceiling,
+    )?)
+}
 
-        if ceiling {
-            // Adjust for ceiling by adding divisor - 1 before division
-            let adjusted_result = numerator.checked_add(divisor - 1)?.checked_div(divisor)?;
-            Some(adjusted_result)
-        } else {
-            // Direct division for floor rounding
-            let adjusted_result = numerator.checked_div(divisor)?;
-            Some(adjusted_result)
-        }
-    };
+// inverse of shares_earned rounding down
+// returns the minimum usd value that earns at least `shares` shares
+// undefined for an empty vault, where any deposit earns bootstrap_shares
+pub fn shares_earned_inverse(shares: u64, shares_supply: u64, vault_tvl: u128) -> Result<u128> {
+    checked_ceil_div(
+        checked_mul(shares as u128, vault_tvl)?,
+        nonzero_supply(shares_supply, vault_tvl)?,
+    )
+}
 
-    result
+// inverse of usd_earned
+// returns the minimum amount of shares to redeem to earn at least `usd_value`
+pub fn usd_earned_inverse(usd_value: u128, shares_supply: u64, vault_tvl: u128) -> Result<u64> {
+    let shares = checked_ceil_div(
+        checked_mul(usd_value, nonzero_supply(shares_supply, vault_tvl)?)?,
+        vault_tvl,
+    )?;
+    to_u64(shares)
 }
 
-pub fn calc_token_amount(
+// inverse of calc_usd_amount rounding down
+// returns the minimum token amount worth at least `scaled_usd_amount`
+pub fn calc_usd_amount_inverse(
     scaled_usd_amount: u128,
     token_decimal: u8,
     price_feed_price: i64,
     price_feed_expo: i32,
-    ceiling: bool,
-) -> Option<u64> {
-    if price_feed_expo >= 0 {
-        return None;
+) -> Result<u64> {
+    to_u64(to_tokens(
+        scaled_usd_amount,
+        unsigned_price(price_feed_price)?,
+        usd_exponent(token_decimal, price_feed_expo),
+        true,
+    )?)
+}
+
+// inverse of calc_token_amount rounding down
+// returns the minimum usd value that converts to at least `token_amount`
+pub fn calc_token_amount_inverse(
+    token_amount: u64,
+    token_decimal: u8,
+    price_feed_price: i64,
+    price_feed_expo: i32,
+) -> Result<u128> {
+    to_usd(
+        token_amount as u128,
+        unsigned_price(price_feed_price)?,
+        usd_exponent(token_decimal, price_feed_expo),
+        true,
+    )
+}
+
+pub(crate) const PRECISION: u8 = 9;
+
+// ui amount of shares minted by the first deposit into an empty vault
+const BOOTSTRAP_SHARES_UI: u128 = 100;
+
+fn checked_mul(a: u128, b: u128) -> Result<u128> {
+    a.checked_mul(b).ok_or(CarrotAmmError::MathOverflow.into())
+}
+
+fn checked_div(numerator: u128, denominator: u128) -> Result<u128> {
+    numerator
+        .checked_div(denominator)
+        .ok_or(CarrotAmmError::DivisionByZero.into())
+}
+
+fn checked_ceil_div(numerator: u128, denominator: u128) -> Result<u128> {
+    if denominator == 0 {
+        return Err(CarrotAmmError::DivisionByZero.into());
     }
 
-    let price_feed_price = price_feed_price.abs() as u128;
+    Ok(numerator.div_ceil(denominator))
+}
 
-    // Handle exponent adjustment for result based on the expo sign
-    let result = {
-        let multiplier = 10_u128.pow((-price_feed_expo) as u32);
-        let temp_result = scaled_usd_amount.checked_mul(multiplier)?;
-        let adjusted_result = if ceiling {
-            temp_result.checked_add(price_feed_price - 1)?
-        } else {
-            temp_result
-        };
-        adjusted_result.checked_div(price_feed_price)
-    }?;
-
-    // Adjust for token decimals
-    let divisor = 10_u128.pow((PRECISION - token_decimal) as u32);
-    let token_amount = if ceiling {
-        result.checked_add(divisor - 1)?.checked_div(divisor)
+fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| CarrotAmmError::MathOverflow.into())
+}
+
+fn pow10(exponent: u32) -> Result<u128> {
+    10_u128
+        .checked_pow(exponent)
+        .ok_or(CarrotAmmError::MathOverflow.into())
+}
+
+// the inverses divide by the supply, an empty vault has no share price to invert
+fn nonzero_supply(shares_supply: u64, vault_tvl: u128) -> Result<u128> {
+    if vault_tvl.le(&0) || shares_supply.le(&0) {
+        return Err(CarrotAmmError::DivisionByZero.into());
+    }
+
+    Ok(shares_supply as u128)
+}
+
+// usd prices are never negative, a negative price is a bad feed rather than a sign to drop
+fn unsigned_price(price_feed_price: i64) -> Result<u128> {
+    u128::try_from(price_feed_price).map_err(|_| CarrotAmmError::NegativePrice.into())
+}
+
+// power of ten taking token amount * price to usd at PRECISION decimals
+// pyth publishes price_feed_price * 10^expo, so any expo and any token decimals fold into one exponent
+// mints with more than PRECISION decimals scale down rather than underflowing PRECISION - decimals
+fn usd_exponent(token_decimal: u8, price_feed_expo: i32) -> i64 {
+    PRECISION as i64 - token_decimal as i64 + price_feed_expo as i64
+}
+
+// token_amount * price * 10^exponent
+fn to_usd(token_amount: u128, price: u128, exponent: i64, ceiling: bool) -> Result<u128> {
+    let value = checked_mul(token_amount, price)?;
+    if exponent >= 0 {
+        mul_pow10(value, exponent.unsigned_abs())
     } else {
-        result.checked_div(divisor)
-    }?;
+        Ok(div_pow10(value, exponent.unsigned_abs(), ceiling))
+    }
+}
 
-    // Ensure the result fits within u64
-    if token_amount > u64::MAX as u128 {
-        None
+// usd / (price * 10^exponent), scaling up before dividing so a single rounding step applies
+// when scaling down, nested floor or ceil divisions round the same as one division
+fn to_tokens(usd: u128, price: u128, exponent: i64, ceiling: bool) -> Result<u128> {
+    let divide = |numerator| {
+        if ceiling {
+            checked_ceil_div(numerator, price)
+        } else {
+            checked_div(numerator, price)
+        }
+    };
+
+    if exponent <= 0 {
+        divide(mul_pow10(usd, exponent.unsigned_abs())?)
     } else {
-        Some(token_amount as u64)
+        Ok(div_pow10(divide(usd)?, exponent.unsigned_abs(), ceiling))
     }
 }
 
-const PRECISION: u8 = 9;
+// value * 10^exponent, zero stays zero however large the power of ten
+fn mul_pow10(value: u128, exponent: u64) -> Result<u128> {
+    if value == 0 {
+        return Ok(0);
+    }
+
+    let exponent = u32::try_from(exponent).map_err(|_| CarrotAmmError::MathOverflow)?;
+    checked_mul(value, pow10(exponent)?)
+}
 
-fn ui_to_amount(ui: f64, decimal: u8) -> Option<u64> {
-    // Convert the floating-point to an integer without losing precision
-    let multiplier = 10u64.checked_pow(decimal as u32)?;
-    let ui_int = (ui * multiplier as f64).round() as u64;
+// value / 10^exponent, a power of ten beyond u128 exceeds any value so the quotient is 0
+fn div_pow10(value: u128, exponent: u64, ceiling: bool) -> u128 {
+    let divisor = u32::try_from(exponent)
+        .ok()
+        .and_then(|exponent| 10_u128.checked_pow(exponent));
 
-    // Check for overflow during conversion
-    ui_int.checked_mul(multiplier)
+    match divisor {
+        Some(divisor) if ceiling => value.div_ceil(divisor),
+        Some(divisor) => value / divisor,
+        None => u128::from(ceiling && value > 0),
+    }
 }
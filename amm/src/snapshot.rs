@@ -0,0 +1,92 @@
+use anyhow::Result;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+use crate::{errors::CarrotAmmError, state::Fee};
+
+// vault nav at a point in time, usd amounts have 9 decimals like the vault tvl
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VaultSnapshot {
+    pub slot: u64,
+    pub timestamp: i64,
+    pub vault_tvl: u128,
+    pub adjusted_shares_supply: u64,
+    // usd value of one share
+    pub nav: u128,
+}
+
+impl VaultSnapshot {
+    // realized yield from holding a share between start and self
+    pub fn yield_since(&self, start: &VaultSnapshot) -> Result<YieldEstimate> {
+        let elapsed_seconds = self.timestamp.saturating_sub(start.timestamp);
+        if elapsed_seconds <= 0 || start.nav == 0 {
+            return Err(CarrotAmmError::InsufficientSnapshots.into());
+        }
+
+        let start_nav =
+            Decimal::from_u128(start.nav).ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+        let end_nav =
+            Decimal::from_u128(self.nav).ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+        let period_return = end_nav
+            .checked_sub(start_nav)
+            .and_then(|delta| delta.checked_div(start_nav))
+            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+
+        // periods per year
+        let periods = Fee::SECONDS_IN_YEAR / elapsed_seconds as f64;
+
+        let apr = Decimal::from_f64(periods)
+            .and_then(|periods| period_return.checked_mul(periods))
+            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+
+        // compounded, ie (1 + r) ^ periods - 1
+        // none when a short window compounds past what f64 or decimal can hold
+        let growth = end_nav
+            .checked_div(start_nav)
+            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+        let apy = f64::try_from(growth)
+            .ok()
+            .map(|growth| growth.powf(periods) - 1.0)
+            .filter(|apy| apy.is_finite())
+            .and_then(Decimal::from_f64);
+
+        Ok(YieldEstimate {
+            start: *start,
+            end: *self,
+            elapsed_seconds,
+            period_return,
+            apr,
+            apy,
+        })
+    }
+}
+
+// returns are fractions, 0.05 is 5%
+#[derive(Clone, Copy, Debug)]
+pub struct YieldEstimate {
+    pub start: VaultSnapshot,
+    pub end: VaultSnapshot,
+    pub elapsed_seconds: i64,
+    pub period_return: Decimal,
+    pub apr: Decimal,
+    // none when the compounded return does not fit, use apr or period_return instead
+    pub apy: Option<Decimal>,
+}
+
+// estimates yield over the last window_seconds using the latest snapshot as the end
+// the start is the most recent snapshot at least window_seconds older than the end
+// snapshots do not need to be sorted
+pub fn estimate_yield(snapshots: &[VaultSnapshot], window_seconds: i64) -> Result<YieldEstimate> {
+    let end = snapshots
+        .iter()
+        .max_by_key(|s| s.timestamp)
+        .ok_or(CarrotAmmError::InsufficientSnapshots)?;
+
+    let window_start = end.timestamp.saturating_sub(window_seconds);
+    let start = snapshots
+        .iter()
+        .filter(|s| s.timestamp.le(&window_start))
+        .max_by_key(|s| s.timestamp)
+        .ok_or(CarrotAmmError::InsufficientSnapshots)?;
+
+    end.yield_since(start)
+}
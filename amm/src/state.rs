@@ -1,7 +1,11 @@
-use anyhow::{anyhow, Result};
-use solana_sdk::pubkey::Pubkey;
+use anyhow::Result;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use spl_token_2022::extension::{
+    interest_bearing_mint::InterestBearingConfig, transfer_fee::TransferFee,
+};
 
-use crate::{calc_usd_amount, errors::CarrotAmmError, shares_earned};
+use crate::{calc_usd_amount, constants::CARROT_PROGRAM, errors::CarrotAmmError, shares_earned};
 
 //
 // accounts
@@ -20,7 +24,14 @@ pub struct Vault {
 }
 
 impl Vault {
+    // sha256("account:Vault")[..8]
+    pub const DISCRIMINATOR: [u8; 8] = [0xd3, 0x08, 0xe8, 0x2b, 0x02, 0x98, 0x75, 0x77];
+
     pub fn load(account_data: &[u8]) -> Result<Self> {
+        // fixed size fields up to and including the assets vec length
+        check_account_len(account_data, 8 + 32 + 32 + Fee::SPACE + 1 + 2 + 2 + 4)?;
+        check_account_discriminator(account_data, &Self::DISCRIMINATOR)?;
+
         let mut offset = 8; // start at 8 to skip anchor account discriminator
 
         // Read fixed size fields
@@ -46,6 +57,12 @@ impl Vault {
         // Dynamic Vec<Asset> deserialization
         let assets_len = u32::from_le_bytes(account_data[offset..offset + 4].try_into()?);
         offset += 4;
+
+        // check the whole vec and the strategies vec length fit before allocating
+        check_account_len(
+            account_data,
+            vec_end(offset, assets_len, Asset::SPACE).saturating_add(4),
+        )?;
         let mut assets = Vec::with_capacity(assets_len as usize);
         for _ in 0..assets_len {
             let asset = Asset::load(&account_data[offset..offset + Asset::SPACE])?;
@@ -56,6 +73,11 @@ impl Vault {
         // Dynamic Vec<StrategyRecord> deserialization
         let strategies_len = u32::from_le_bytes(account_data[offset..offset + 4].try_into()?);
         offset += 4;
+
+        check_account_len(
+            account_data,
+            vec_end(offset, strategies_len, StrategyRecord::SPACE),
+        )?;
         let mut strategies = Vec::with_capacity(strategies_len as usize);
         for _ in 0..strategies_len {
             let strategy =
@@ -78,12 +100,12 @@ impl Vault {
 
     // get total vault balance in usd
     // looks at strategy balances and ATA balances
-    pub fn get_tvl(&self, asset_state: &Vec<AssetState>, ceiling: bool) -> Result<u128> {
+    pub fn get_tvl(&self, asset_state: &[AssetState], ceiling: bool) -> Result<u128> {
         let total_strategy_balance: u128 = self
             .strategies
             .iter()
             .map(|strat| {
-                let state = get_asset_state_by_id(&asset_state, strat.asset_id)?;
+                let state = get_asset_state_by_id(asset_state, strat.asset_id)?;
                 let balance_usd = strat.get_balance_usd(state, ceiling)?;
                 Ok(balance_usd)
             })
@@ -95,7 +117,7 @@ impl Vault {
             .assets
             .iter()
             .map(|asset| {
-                let state = get_asset_state_by_id(&asset_state, asset.asset_id)?;
+                let state = get_asset_state_by_id(asset_state, asset.asset_id)?;
                 let balance_usd = asset.get_balance_usd(state, ceiling)?;
                 Ok(balance_usd)
             })
@@ -110,10 +132,11 @@ impl Vault {
 
     pub fn calculate_accumulated_performance_fee(
         &self,
-        asset_state: &Vec<AssetState>,
+        asset_state: &[AssetState],
         shares_supply: u64,
         shares_decimals: u8,
         vault_tvl: u128,
+        round_up: bool,
     ) -> Result<u64> {
         let mut performance_fee_accumulated: u64 = 0;
         for strategy in self.strategies.iter() {
@@ -123,7 +146,7 @@ impl Vault {
             // calculate performance fee for each strategy
             let strategy_performance_fee = self.fee.calculate_performance_fee(
                 strategy.net_earnings,
-                asset.oracle_price,
+                asset.get_price(round_up),
                 asset.oracle_price_expo,
                 asset.mint_decimals,
                 shares_supply,
@@ -138,6 +161,76 @@ impl Vault {
         Ok(performance_fee_accumulated)
     }
 
+    // breakdown of the tvl per asset, split between the reserve ata and each strategy
+    // balances are valued like get_tvl with ceiling = false so the totals match
+    pub fn allocation(&self, asset_state: &[AssetState]) -> Result<VaultAllocation> {
+        let mut assets = Vec::with_capacity(self.assets.len());
+        for asset in self.assets.iter() {
+            let state = get_asset_state_by_id(asset_state, asset.asset_id)?;
+
+            let strategies = self
+                .strategies
+                .iter()
+                .filter(|strat| strat.asset_id.eq(&asset.asset_id))
+                .map(|strat| {
+                    Ok(StrategyAllocation {
+                        strategy_id: strat.strategy_id,
+                        asset_id: strat.asset_id,
+                        balance: strat.balance,
+                        balance_usd: strat.get_balance_usd(state, false)?,
+                        tvl_share: Decimal::ZERO,
+                        net_earnings: strat.net_earnings,
+                    })
+                })
+                .collect::<Result<Vec<StrategyAllocation>>>()?;
+
+            assets.push(AssetAllocation {
+                asset_id: asset.asset_id,
+                mint: asset.mint,
+                reserve_balance: state.ata_amount,
+                reserve_balance_usd: asset.get_balance_usd(state, false)?,
+                reserve_tvl_share: Decimal::ZERO,
+                strategies,
+                total_balance_usd: 0,
+                tvl_share: Decimal::ZERO,
+            });
+        }
+
+        // strategies whose asset is not in the vault would be missed by get_tvl too
+        for strat in self.strategies.iter() {
+            if !assets.iter().any(|a| a.asset_id.eq(&strat.asset_id)) {
+                return Err(CarrotAmmError::AssetNotFound.into());
+            }
+        }
+
+        for asset in assets.iter_mut() {
+            asset.total_balance_usd = asset
+                .strategies
+                .iter()
+                .try_fold(asset.reserve_balance_usd, |acc, strat| {
+                    acc.checked_add(strat.balance_usd)
+                })
+                .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+        }
+
+        let tvl = assets
+            .iter()
+            .try_fold(0_u128, |acc, asset| {
+                acc.checked_add(asset.total_balance_usd)
+            })
+            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+
+        for asset in assets.iter_mut() {
+            asset.reserve_tvl_share = tvl_share(asset.reserve_balance_usd, tvl)?;
+            asset.tvl_share = tvl_share(asset.total_balance_usd, tvl)?;
+            for strat in asset.strategies.iter_mut() {
+                strat.tvl_share = tvl_share(strat.balance_usd, tvl)?;
+            }
+        }
+
+        Ok(VaultAllocation { tvl, assets })
+    }
+
     pub fn get_asset_by_mint(&self, asset_mint: Pubkey) -> Result<&Asset> {
         let asset = self
             .assets
@@ -148,11 +241,95 @@ impl Vault {
     }
 }
 
+// allocation report, usd values use the same precision as get_tvl
+#[derive(Clone, Debug)]
+pub struct VaultAllocation {
+    pub tvl: u128,
+    pub assets: Vec<AssetAllocation>,
+}
+
+impl VaultAllocation {
+    pub fn get_asset(&self, asset_id: u16) -> Option<&AssetAllocation> {
+        self.assets.iter().find(|a| a.asset_id.eq(&asset_id))
+    }
+
+    pub fn get_strategy(&self, strategy_id: u16) -> Option<&StrategyAllocation> {
+        self.strategies().find(|s| s.strategy_id.eq(&strategy_id))
+    }
+
+    pub fn strategies(&self) -> impl Iterator<Item = &StrategyAllocation> {
+        self.assets.iter().flat_map(|a| a.strategies.iter())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AssetAllocation {
+    pub asset_id: u16,
+    pub mint: Pubkey,
+    // tokens held in the vault ata
+    pub reserve_balance: u64,
+    pub reserve_balance_usd: u128,
+    pub reserve_tvl_share: Decimal,
+    pub strategies: Vec<StrategyAllocation>,
+    // reserve plus strategies
+    pub total_balance_usd: u128,
+    pub tvl_share: Decimal,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StrategyAllocation {
+    pub strategy_id: u16,
+    pub asset_id: u16,
+    pub balance: u64,
+    pub balance_usd: u128,
+    pub tvl_share: Decimal,
+    pub net_earnings: i64,
+}
+
+// fraction of the tvl in [0, 1], zero for an empty vault
+fn tvl_share(balance_usd: u128, tvl: u128) -> Result<Decimal> {
+    if tvl == 0 {
+        return Ok(Decimal::ZERO);
+    }
+
+    let balance_usd =
+        Decimal::from_u128(balance_usd).ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+    let tvl = Decimal::from_u128(tvl).ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+    balance_usd
+        .checked_div(tvl)
+        .ok_or(CarrotAmmError::InvalidTokenCalculation.into())
+}
+
+#[derive(Clone, Debug)]
 pub struct Strategy {
     pub metadata: StrategyMetadata,
     pub strategy_type: StrategyType,
 }
 
+impl Strategy {
+    // sha256("account:Strategy")[..8]
+    pub const DISCRIMINATOR: [u8; 8] = [0xae, 0x6e, 0x27, 0x77, 0x52, 0x6a, 0xa9, 0x66];
+
+    // strategy accounts must be owned by the carrot program
+    pub fn load(address: &Pubkey, account: &Account) -> Result<Self> {
+        check_account_owner(address, &account.owner, &[CARROT_PROGRAM])?;
+
+        let account_data = account.data.as_slice();
+        check_account_len(account_data, 8)?;
+        check_account_discriminator(account_data, &Self::DISCRIMINATOR)?;
+
+        let mut offset = 8; // start at 8 to skip anchor account discriminator
+
+        let metadata = StrategyMetadata::load(account_data, &mut offset)?;
+        let strategy_type = StrategyType::load(account_data, &mut offset)?;
+
+        Ok(Strategy {
+            metadata,
+            strategy_type,
+        })
+    }
+}
+
 // data
 
 #[derive(Clone, Copy, Debug)]
@@ -168,7 +345,7 @@ impl Asset {
     pub const SPACE: usize = 2 + 32 + 1 + 32 + 32;
 
     pub fn load(account_data: &[u8]) -> Result<Self> {
-        assert_eq!(account_data.len(), Self::SPACE);
+        check_account_len(account_data, Self::SPACE)?;
 
         let asset_id = u16::from_le_bytes(account_data[0..2].try_into()?);
         let mint = Pubkey::new_from_array(account_data[2..34].try_into()?);
@@ -189,11 +366,10 @@ impl Asset {
         calc_usd_amount(
             asset_state.ata_amount,
             asset_state.mint_decimals,
-            asset_state.oracle_price,
+            asset_state.get_price(ceiling),
             asset_state.oracle_price_expo,
             ceiling,
         )
-        .ok_or(CarrotAmmError::InvalidTokenCalculation.into())
     }
 }
 
@@ -209,7 +385,7 @@ impl StrategyRecord {
     pub const SPACE: usize = 2 + 2 + 8 + 8;
 
     pub fn load(account_data: &[u8]) -> Result<Self> {
-        assert_eq!(account_data.len(), Self::SPACE);
+        check_account_len(account_data, Self::SPACE)?;
 
         let strategy_id = u16::from_le_bytes(account_data[0..2].try_into()?);
         let asset_id = u16::from_le_bytes(account_data[2..4].try_into()?);
@@ -228,14 +404,14 @@ impl StrategyRecord {
         calc_usd_amount(
             self.balance,
             asset_state.mint_decimals,
-            asset_state.oracle_price,
+            asset_state.get_price(ceiling),
             asset_state.oracle_price_expo,
             ceiling,
         )
-        .ok_or(CarrotAmmError::InvalidTokenCalculation.into())
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct StrategyMetadata {
     pub name: String,
     pub strategy_id: u16,
@@ -243,6 +419,28 @@ pub struct StrategyMetadata {
     pub vault: Pubkey,
 }
 
+impl StrategyMetadata {
+    // metadata has a borsh string so it is read in place, advancing offset
+    fn load(account_data: &[u8], offset: &mut usize) -> Result<Self> {
+        let name_len = read_u32(account_data, offset)?;
+        let name_end = vec_end(*offset, name_len, 1);
+        check_account_len(account_data, name_end)?;
+        let name = String::from_utf8(account_data[*offset..name_end].to_vec())?;
+        *offset = name_end;
+
+        let strategy_id = read_u16(account_data, offset)?;
+        let asset_mint = read_pubkey(account_data, offset)?;
+        let vault = read_pubkey(account_data, offset)?;
+
+        Ok(StrategyMetadata {
+            name,
+            strategy_id,
+            asset_mint,
+            vault,
+        })
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Fee {
     pub redemption_fee_bps: u16,
@@ -257,10 +455,10 @@ impl Fee {
     // Assuming the SPACE constant for Fee is defined as the sum of its fields' sizes
     pub const SPACE: usize = 2 + 8 + 2 + 8 + 8 + 2; // Example, adjust based on actual sizes
 
-    const SECONDS_IN_YEAR: f64 = 31557600.0;
+    pub const SECONDS_IN_YEAR: f64 = 31557600.0;
 
     pub fn load(account_data: &[u8]) -> Result<Self> {
-        assert_eq!(account_data.len(), Self::SPACE);
+        check_account_len(account_data, Self::SPACE)?;
 
         let mut offset = 0;
 
@@ -298,14 +496,14 @@ impl Fee {
 
     // returns the number of shares that should be minted to the fee account
     // increments accumulated store
+    // current_time is the unix timestamp of the on-chain clock
     pub fn calculate_management_fee(
         &self,
         tvl: u128,
         shares_supply: u64,
         shares_decimals: u8,
+        current_time: i64,
     ) -> Result<u64> {
-        let current_time = chrono::Utc::now().timestamp();
-
         // require a delta of over 60 seconds
         let time_delta = current_time - self.management_fee_last_update;
         if time_delta <= 60 {
@@ -327,8 +525,8 @@ impl Fee {
         }
 
         // convert usd cents to shares ui based on NAV
-        let shares_amount = shares_earned(fee_usd_cents, shares_supply, shares_decimals, tvl, true)
-            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+        let shares_amount =
+            shares_earned(fee_usd_cents, shares_supply, shares_decimals, tvl, true)?;
 
         Ok(shares_amount)
     }
@@ -356,8 +554,7 @@ impl Fee {
             asset_price,
             asset_price_expo,
             true,
-        )
-        .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+        )?;
 
         // calculate performance fee in usd
         let fee_amount_usd = self.calc_performance_fee(net_earnings_usd)?;
@@ -369,7 +566,6 @@ impl Fee {
             vault_tvl,
             true,
         )
-        .ok_or(CarrotAmmError::InvalidTokenCalculation.into())
     }
 
     // returns (remaining_amount after fee, fee_amount)
@@ -381,6 +577,30 @@ impl Fee {
         self.calc_redemption_fee(redemption_amount)
     }
 
+    // inverse of calculate_redemption_fee
+    // returns (minimum redemption amount leaving at least remaining_amount after fee, fee_amount)
+    pub fn calculate_redemption_fee_inverse(&self, remaining_amount: u64) -> Result<(u64, u64)> {
+        if self.redemption_fee_bps == 0 {
+            return Ok((remaining_amount, 0));
+        }
+
+        // remaining = amount - ceil(amount * bps / 10_000) = floor(amount * (10_000 - bps) / 10_000)
+        let remaining_bps = 10_000_u128
+            .checked_sub(self.redemption_fee_bps as u128)
+            .filter(|bps| bps.gt(&0))
+            .ok_or(CarrotAmmError::InvalidFeeCalculation)?;
+
+        let redemption_amount = (remaining_amount as u128)
+            .checked_mul(10_000)
+            .map(|prod| prod.div_ceil(remaining_bps))
+            .and_then(|amount| u64::try_from(amount).ok())
+            .ok_or(CarrotAmmError::InvalidTokenCalculation)?;
+
+        let (_, fee_amount) = self.calc_redemption_fee(redemption_amount)?;
+
+        Ok((redemption_amount, fee_amount))
+    }
+
     // inflates the shares_supply by the amount of unrealized fees accrued by the protocol
     // performance fees is computed inside the ix, which is why we pass it in
     pub fn adjust_shares_by_fees(
@@ -426,6 +646,7 @@ impl Fee {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum StrategyType {
     MarginfiSupply {
         account: Pubkey,
@@ -480,23 +701,217 @@ pub enum StrategyType {
         spot_market_vault: Pubkey,
         market_index: u16,
     },
-    Chest {
-        chest: Pubkey,
-        coin: Pubkey,
-        drift_vault: Pubkey,
-        coin_token_account: Pubkey,
-        spot_market_index: u16,
-    },
 }
 
-#[derive(Clone, Copy)]
+impl StrategyType {
+    // borsh enum, a one byte variant index followed by the variant fields
+    // variants follow the order of the program's deserializer, which accepts 0 to 5
+    fn load(account_data: &[u8], offset: &mut usize) -> Result<Self> {
+        check_account_len(account_data, *offset + 1)?;
+        let variant = account_data[*offset];
+        *offset += 1;
+
+        let strategy_type = match variant {
+            0 => StrategyType::MarginfiSupply {
+                account: read_pubkey(account_data, offset)?,
+                group: read_pubkey(account_data, offset)?,
+                bank: read_pubkey(account_data, offset)?,
+                bank_liquidity_vault: read_pubkey(account_data, offset)?,
+                bank_liquidity_vault_authority: read_pubkey(account_data, offset)?,
+                oracle: read_pubkey(account_data, offset)?,
+            },
+            1 => StrategyType::KlendSupply {
+                reserve: read_pubkey(account_data, offset)?,
+                reserve_collateral_mint: read_pubkey(account_data, offset)?,
+                reserve_liquidity_supply: read_pubkey(account_data, offset)?,
+                reserve_destination_deposit_collateral: read_pubkey(account_data, offset)?,
+                reserve_farm_state: read_pubkey(account_data, offset)?,
+                lending_market: read_pubkey(account_data, offset)?,
+                oracle: read_pubkey(account_data, offset)?,
+                scope_prices: read_pubkey(account_data, offset)?,
+            },
+            2 => StrategyType::SolendSupply {
+                reserve: read_pubkey(account_data, offset)?,
+                reserve_collateral_mint: read_pubkey(account_data, offset)?,
+                reserve_liquidity_supply: read_pubkey(account_data, offset)?,
+                deposit_collateral_ata: read_pubkey(account_data, offset)?,
+                lending_market: read_pubkey(account_data, offset)?,
+                lending_market_authority: read_pubkey(account_data, offset)?,
+                pyth_oracle: read_pubkey(account_data, offset)?,
+                switchboard_oracle: read_pubkey(account_data, offset)?,
+            },
+            3 => StrategyType::MangoSupply {
+                group: read_pubkey(account_data, offset)?,
+                account: read_pubkey(account_data, offset)?,
+                bank: read_pubkey(account_data, offset)?,
+                vault: read_pubkey(account_data, offset)?,
+                pyth_oracle: read_pubkey(account_data, offset)?,
+                switchboard_oracle: read_pubkey(account_data, offset)?,
+            },
+            4 => StrategyType::DriftSupply {
+                state: read_pubkey(account_data, offset)?,
+                signer: read_pubkey(account_data, offset)?,
+                spot_market: read_pubkey(account_data, offset)?,
+                spot_market_vault: read_pubkey(account_data, offset)?,
+                perp_market: read_pubkey(account_data, offset)?,
+                spot_pyth_oracle: read_pubkey(account_data, offset)?,
+                perp_pyth_oracle: read_pubkey(account_data, offset)?,
+                sub_account_id: read_u16(account_data, offset)?,
+                market_index: read_u16(account_data, offset)?,
+            },
+            5 => StrategyType::DriftInsuranceFund {
+                state: read_pubkey(account_data, offset)?,
+                spot_market: read_pubkey(account_data, offset)?,
+                spot_market_vault: read_pubkey(account_data, offset)?,
+                market_index: read_u16(account_data, offset)?,
+            },
+            _ => return Err(CarrotAmmError::UnknownStrategyType.into()),
+        };
+
+        Ok(strategy_type)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 pub struct AssetState {
     pub asset_id: u16,
     pub mint: Pubkey,
     pub mint_decimals: u8,
+    // owning token programs of the asset mint and the vault ata, used for the swap accounts
+    pub mint_token_program: Pubkey,
+    pub ata_token_program: Pubkey,
+    pub ata: Pubkey,
     pub ata_amount: u64,
-    pub oracle_price: i64,
+    pub oracle: Pubkey,
+    pub oracle_price_up: i64,
+    pub oracle_price_down: i64,
     pub oracle_price_expo: i32,
+    pub oracle_price_feed: PriceFeedMessage,
+    // token-2022 transfer fee of the asset mint for the current epoch
+    pub transfer_fee: Option<TransferFee>,
+    // token-2022 interest bearing config of the asset mint
+    // only changes ui amounts, raw amounts are what the program transfers and prices
+    pub interest_bearing: Option<InterestBearingConfig>,
+}
+
+impl AssetState {
+    // amount received when amount is transferred, net of the transfer fee
+    pub fn amount_after_transfer_fee(&self, amount: u64) -> Result<u64> {
+        match self.transfer_fee {
+            Some(transfer_fee) => transfer_fee
+                .calculate_post_fee_amount(amount)
+                .ok_or(CarrotAmmError::InvalidFeeCalculation.into()),
+            None => Ok(amount),
+        }
+    }
+
+    // amount to transfer so that amount is received, inverse of amount_after_transfer_fee
+    pub fn amount_before_transfer_fee(&self, amount: u64) -> Result<u64> {
+        match self.transfer_fee {
+            Some(transfer_fee) => transfer_fee
+                .calculate_pre_fee_amount(amount)
+                .ok_or(CarrotAmmError::InvalidFeeCalculation.into()),
+            None => Ok(amount),
+        }
+    }
+
+    // price used when rounding in the given direction, issue rounds up and redeem rounds down
+    pub fn get_price(&self, round_up: bool) -> i64 {
+        if round_up {
+            self.oracle_price_up
+        } else {
+            self.oracle_price_down
+        }
+    }
+}
+
+// errors instead of panicking when an account is shorter than its layout
+fn check_account_len(account_data: &[u8], expected: usize) -> Result<()> {
+    if account_data.len().lt(&expected) {
+        return Err(CarrotAmmError::AccountDataTooShort {
+            expected,
+            actual: account_data.len(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+// errors if the account is not owned by one of the expected programs
+pub(crate) fn check_account_owner(
+    address: &Pubkey,
+    owner: &Pubkey,
+    expected_owners: &[Pubkey],
+) -> Result<()> {
+    if !expected_owners.contains(owner) {
+        return Err(CarrotAmmError::InvalidAccountOwner {
+            account: *address,
+            owner: *owner,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+// errors if the account does not start with the expected anchor discriminator
+fn check_account_discriminator(account_data: &[u8], discriminator: &[u8; 8]) -> Result<()> {
+    if account_data[..8].ne(discriminator) {
+        return Err(CarrotAmmError::InvalidAccountDiscriminator.into());
+    }
+
+    Ok(())
+}
+
+// end offset of a borsh vec of len items starting at offset, saturates for garbage lengths
+fn vec_end(offset: usize, len: u32, item_space: usize) -> usize {
+    (len as usize)
+        .saturating_mul(item_space)
+        .saturating_add(offset)
+}
+
+// readers for variable length accounts, each checks bounds and advances offset
+pub(crate) fn read_u8(account_data: &[u8], offset: &mut usize) -> Result<u8> {
+    check_account_len(account_data, *offset + 1)?;
+    let value = account_data[*offset];
+    *offset += 1;
+    Ok(value)
+}
+
+pub(crate) fn read_u16(account_data: &[u8], offset: &mut usize) -> Result<u16> {
+    check_account_len(account_data, *offset + 2)?;
+    let value = u16::from_le_bytes(account_data[*offset..*offset + 2].try_into()?);
+    *offset += 2;
+    Ok(value)
+}
+
+pub(crate) fn read_u32(account_data: &[u8], offset: &mut usize) -> Result<u32> {
+    check_account_len(account_data, *offset + 4)?;
+    let value = u32::from_le_bytes(account_data[*offset..*offset + 4].try_into()?);
+    *offset += 4;
+    Ok(value)
+}
+
+pub(crate) fn read_u64(account_data: &[u8], offset: &mut usize) -> Result<u64> {
+    check_account_len(account_data, *offset + 8)?;
+    let value = u64::from_le_bytes(account_data[*offset..*offset + 8].try_into()?);
+    *offset += 8;
+    Ok(value)
+}
+
+pub(crate) fn read_u128(account_data: &[u8], offset: &mut usize) -> Result<u128> {
+    check_account_len(account_data, *offset + 16)?;
+    let value = u128::from_le_bytes(account_data[*offset..*offset + 16].try_into()?);
+    *offset += 16;
+    Ok(value)
+}
+
+pub(crate) fn read_pubkey(account_data: &[u8], offset: &mut usize) -> Result<Pubkey> {
+    check_account_len(account_data, *offset + 32)?;
+    let value = Pubkey::new_from_array(account_data[*offset..*offset + 32].try_into()?);
+    *offset += 32;
+    Ok(value)
 }
 
 pub fn get_asset_state_by_id(asset_state: &[AssetState], asset_id: u16) -> Result<&AssetState> {
@@ -527,8 +942,12 @@ pub struct PriceUpdateV2 {
 impl PriceUpdateV2 {
     pub const SPACE: usize = 8 + 32 + 2 + 32 + 8 + 8 + 4 + 8 + 8 + 8 + 8 + 8;
 
+    // sha256("account:PriceUpdateV2")[..8]
+    pub const DISCRIMINATOR: [u8; 8] = [0x22, 0xf1, 0x23, 0x63, 0x9d, 0x7e, 0xf4, 0xcd];
+
     pub fn load(account_data: &[u8]) -> Result<Self> {
-        assert_eq!(account_data.len(), Self::SPACE);
+        check_account_len(account_data, Self::SPACE)?;
+        check_account_discriminator(account_data, &Self::DISCRIMINATOR)?;
         let mut offset = 8;
 
         let write_authority = Pubkey::new_from_array(account_data[offset..offset + 32].try_into()?);
@@ -546,7 +965,7 @@ impl PriceUpdateV2 {
                 offset += 1; // Move past the num_signatures byte
                 VerificationLevel::Partial { num_signatures }
             }
-            _ => return Err(anyhow!("Unknown verification level byte")),
+            _ => return Err(CarrotAmmError::UnknownVerificationLevel.into()),
         };
 
         let feed_id = account_data[offset..offset + 32].try_into()?;
@@ -592,15 +1011,32 @@ impl PriceUpdateV2 {
         })
     }
 
-    // Updated get_price_usd_from_pyth_oracle function
+    // rejects updates for another feed or verified by too few guardian signatures
+    // mirrors the checks of the pyth receiver sdk used by the program
+    pub fn check_price_feed(
+        &self,
+        expected_feed_id: &FeedId,
+        min_verification_level: VerificationLevel,
+    ) -> Result<()> {
+        if self.price_message.feed_id.ne(expected_feed_id) {
+            return Err(CarrotAmmError::OracleFeedIdMismatch.into());
+        }
+
+        if !self.verification_level.gte(min_verification_level) {
+            return Err(CarrotAmmError::OracleVerificationLevelTooLow.into());
+        }
+
+        Ok(())
+    }
+
+    // current_time is the unix timestamp of the on-chain clock in seconds
     pub fn get_price_usd_from_pyth_oracle(
         &self,
         oracle_max_age: u64,
+        price_source: PriceSource,
         rounding_mode: RoundingMode,
+        current_time: i64,
     ) -> Result<(i64, i32)> {
-        // get current time in seconds
-        let current_time = chrono::Utc::now().timestamp();
-
         // determine how old the price is in seconds
         let age = current_time.saturating_sub(self.price_message.publish_time) as u64;
 
@@ -609,30 +1045,35 @@ impl PriceUpdateV2 {
             return Err(CarrotAmmError::OraclePriceStale.into());
         }
 
-        // Adjust the price by the confidence value based on rounding mode
-        let adjusted_price = match rounding_mode {
-            RoundingMode::RoundUp => self
-                .price_message
-                .ema_price
-                .saturating_add(self.price_message.ema_conf as i64),
-            RoundingMode::RoundDown => self
-                .price_message
-                .ema_price
-                .saturating_sub(self.price_message.ema_conf as i64),
-            RoundingMode::Avg => self.price_message.ema_price,
-        };
+        let price = self.price_message.get_price(price_source, rounding_mode);
 
-        Ok((adjusted_price, self.price_message.exponent))
+        Ok((price, self.price_message.exponent))
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum VerificationLevel {
     Partial { num_signatures: u8 },
     Full,
 }
 
-#[derive(Clone, Copy)]
+impl VerificationLevel {
+    // full verification satisfies any level, partial needs at least as many signatures
+    pub fn gte(&self, other: VerificationLevel) -> bool {
+        match (self, other) {
+            (VerificationLevel::Full, _) => true,
+            (VerificationLevel::Partial { .. }, VerificationLevel::Full) => false,
+            (
+                VerificationLevel::Partial { num_signatures },
+                VerificationLevel::Partial {
+                    num_signatures: other_num_signatures,
+                },
+            ) => num_signatures.ge(&other_num_signatures),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 pub struct PriceFeedMessage {
     pub feed_id: FeedId,
     pub price: i64,
@@ -644,15 +1085,55 @@ pub struct PriceFeedMessage {
     pub ema_conf: u64,
 }
 
+impl PriceFeedMessage {
+    // price for the requested direction according to the price source, scaled by exponent
+    pub fn get_price(&self, price_source: PriceSource, rounding_mode: RoundingMode) -> i64 {
+        match (price_source, rounding_mode) {
+            (PriceSource::Spot, _) => self.price,
+            (PriceSource::Ema, _) => self.ema_price,
+            (PriceSource::Conservative, RoundingMode::RoundUp) => self.price.max(self.ema_price),
+            (PriceSource::Conservative, RoundingMode::RoundDown) => self.price.min(self.ema_price),
+            (PriceSource::ConfidenceAdjusted, RoundingMode::RoundUp) => {
+                self.ema_price.saturating_add(self.ema_conf as i64)
+            }
+            (PriceSource::ConfidenceAdjusted, RoundingMode::RoundDown) => {
+                self.ema_price.saturating_sub(self.ema_conf as i64)
+            }
+            (PriceSource::Conservative | PriceSource::ConfidenceAdjusted, RoundingMode::Avg) => {
+                self.ema_price
+            }
+        }
+    }
+}
+
 pub type FeedId = [u8; 32];
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RoundingMode {
     RoundUp,
     RoundDown,
     Avg,
 }
 
+// which pyth fields price an asset
+// issue quotes price with RoundUp and redeem quotes with RoundDown
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PriceSource {
+    // spot price, same for both directions
+    // opt-in only, quotes will not match the program
+    Spot,
+    // ema price, same for both directions
+    // this is how the program prices issue and redeem
+    #[default]
+    Ema,
+    // larger of spot and ema when rounding up, smaller when rounding down
+    // opt-in only, quotes will not match the program
+    Conservative,
+    // ema price plus ema confidence when rounding up, minus when rounding down
+    // opt-in only, quotes will not match the program
+    ConfidenceAdjusted,
+}
+
 // oracle price max age allowed in seconds
 // matches on chain check
 pub const MAX_AGE: u64 = 300;
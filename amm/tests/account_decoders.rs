@@ -0,0 +1,162 @@
+use amm::constants::{CRT_VAULT, USDC_ORACLE};
+use amm::errors::CarrotAmmError;
+use amm::state::{Asset, Fee, PriceUpdateV2, StrategyRecord, Vault};
+use amm::CarrotAmm;
+use jupiter_amm_interface::Amm;
+
+mod utils;
+use utils::*;
+
+// small deterministic xorshift generator so failures are reproducible
+struct XorShift(u64);
+
+impl XorShift {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next_u64() as u8).collect()
+    }
+}
+
+fn assert_too_short<T>(result: anyhow::Result<T>, expected: usize, actual: usize) {
+    let err = result.err().expect("expected an error");
+    assert_eq!(
+        Some(&CarrotAmmError::AccountDataTooShort { expected, actual }),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
+
+#[tokio::test]
+async fn test_fixed_size_decoders_truncated() {
+    let mut rng = XorShift(0x5eed);
+
+    for len in 0..Asset::SPACE {
+        assert_too_short(Asset::load(&rng.bytes(len)), Asset::SPACE, len);
+    }
+    for len in 0..StrategyRecord::SPACE {
+        assert_too_short(
+            StrategyRecord::load(&rng.bytes(len)),
+            StrategyRecord::SPACE,
+            len,
+        );
+    }
+    for len in 0..Fee::SPACE {
+        assert_too_short(Fee::load(&rng.bytes(len)), Fee::SPACE, len);
+    }
+    for len in 0..PriceUpdateV2::SPACE {
+        assert_too_short(
+            PriceUpdateV2::load(&rng.bytes(len)),
+            PriceUpdateV2::SPACE,
+            len,
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_fixed_size_decoders_random() {
+    let mut rng = XorShift(0xdecade);
+
+    // random content of any length must never panic
+    for _ in 0..10_000 {
+        let len = (rng.next_u64() % 256) as usize;
+        let data = rng.bytes(len);
+
+        let _ = Asset::load(&data);
+        let _ = StrategyRecord::load(&data);
+        let _ = Fee::load(&data);
+        let _ = PriceUpdateV2::load(&data);
+    }
+}
+
+#[tokio::test]
+async fn test_vault_decoder_truncated() {
+    let account_map = load_account_map_from_file();
+    let vault_data = &account_map.get(&CRT_VAULT).unwrap().data;
+
+    Vault::load(vault_data).unwrap();
+
+    // every prefix of the fixture is missing data the layout requires
+    for len in 0..vault_data.len() {
+        let err = Vault::load(&vault_data[..len]).expect_err("expected an error");
+        assert!(
+            matches!(
+                err.downcast_ref::<CarrotAmmError>(),
+                Some(CarrotAmmError::AccountDataTooShort { actual, .. }) if *actual == len
+            ),
+            "len: {}",
+            len
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_vault_decoder_random() {
+    let mut rng = XorShift(0xc0ffee);
+
+    let account_map = load_account_map_from_file();
+    let vault_data = account_map.get(&CRT_VAULT).unwrap().data.clone();
+
+    for _ in 0..10_000 {
+        // fully random buffers
+        let len = (rng.next_u64() % 1024) as usize;
+        let _ = Vault::load(&rng.bytes(len));
+
+        // fixture with a few corrupted bytes, including the vec lengths
+        let mut corrupted = vault_data.clone();
+        for _ in 0..4 {
+            let index = (rng.next_u64() as usize) % corrupted.len();
+            corrupted[index] = rng.next_u64() as u8;
+        }
+        let _ = Vault::load(&corrupted);
+    }
+
+    // huge vec lengths must not allocate or panic
+    let assets_len_offset = 8 + 32 + 32 + Fee::SPACE + 1 + 2 + 2;
+    let mut corrupted = vault_data.clone();
+    corrupted[assets_len_offset..assets_len_offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(Vault::load(&corrupted).is_err());
+}
+
+#[tokio::test]
+async fn test_oracle_decoder_unknown_verification_level() {
+    let account_map = load_account_map_from_file();
+    let mut oracle_data = account_map.get(&USDC_ORACLE).unwrap().data.clone();
+
+    // verification level follows the discriminator and write authority
+    oracle_data[8 + 32] = 0xff;
+
+    let err = PriceUpdateV2::load(&oracle_data).err().unwrap();
+    assert_eq!(
+        Some(&CarrotAmmError::UnknownVerificationLevel),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
+
+#[tokio::test]
+async fn test_update_with_truncated_accounts() {
+    let account_map = load_account_map_from_file();
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    let carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+
+    // truncating any account the amm reads must never panic
+    // and an account missing its base layout must fail the update
+    for address in carrot_amm.get_accounts_to_update() {
+        let full_len = account_map.get(&address).unwrap().data.len();
+
+        for len in [0, 1, full_len / 2, full_len - 1] {
+            let mut truncated_map = account_map.clone();
+            truncated_map.get_mut(&address).unwrap().data.truncate(len);
+
+            let mut carrot_amm = carrot_amm.clone();
+            let update_result = carrot_amm.update(&truncated_map);
+            if len <= 1 {
+                assert!(update_result.is_err(), "address: {} len: {}", address, len);
+            }
+        }
+    }
+}
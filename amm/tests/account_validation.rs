@@ -0,0 +1,72 @@
+use amm::constants::{CRT_MINT, CRT_VAULT, USDC_ORACLE, USDC_VAULT_ATA};
+use amm::errors::CarrotAmmError;
+use amm::state::{PriceUpdateV2, Vault};
+use amm::CarrotAmm;
+use jupiter_amm_interface::{AccountMap, Amm};
+use solana_sdk::pubkey::Pubkey;
+
+mod utils;
+use utils::*;
+
+fn update_with(account_map: &AccountMap) -> anyhow::Result<()> {
+    let vault_state =
+        Vault::load(&load_account_map_from_file().get(&CRT_VAULT).unwrap().data).unwrap();
+    let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+    carrot_amm.update(account_map)
+}
+
+#[tokio::test]
+async fn test_invalid_account_owner() {
+    for address in [CRT_VAULT, CRT_MINT, USDC_VAULT_ATA, USDC_ORACLE] {
+        let mut account_map = load_account_map_from_file();
+        let owner = Pubkey::new_unique();
+        account_map.get_mut(&address).unwrap().owner = owner;
+
+        let err = update_with(&account_map).unwrap_err();
+        assert_eq!(
+            Some(&CarrotAmmError::InvalidAccountOwner {
+                account: address,
+                owner
+            }),
+            err.downcast_ref::<CarrotAmmError>(),
+            "address: {}",
+            address
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_account_discriminator() {
+    let account_map = load_account_map_from_file();
+
+    // an oracle is not a vault and a vault is not an oracle
+    let oracle_data = &account_map.get(&USDC_ORACLE).unwrap().data;
+    let vault_data = &account_map.get(&CRT_VAULT).unwrap().data;
+
+    let mut vault_with_oracle_discriminator = vault_data.clone();
+    vault_with_oracle_discriminator[..8].copy_from_slice(&oracle_data[..8]);
+    let err = Vault::load(&vault_with_oracle_discriminator).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::InvalidAccountDiscriminator),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+
+    let mut oracle_with_vault_discriminator = oracle_data.clone();
+    oracle_with_vault_discriminator[..8].copy_from_slice(&vault_data[..8]);
+    let err = PriceUpdateV2::load(&oracle_with_vault_discriminator)
+        .err()
+        .unwrap();
+    assert_eq!(
+        Some(&CarrotAmmError::InvalidAccountDiscriminator),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+
+    // a vault account with a bad discriminator fails the update
+    let mut account_map = load_account_map_from_file();
+    account_map.get_mut(&CRT_VAULT).unwrap().data[0] ^= 0xff;
+    let err = update_with(&account_map).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::InvalidAccountDiscriminator),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
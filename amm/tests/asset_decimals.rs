@@ -0,0 +1,143 @@
+use amm::constants::{CRT_MINT, USDC_MINT};
+use amm::CarrotAmm;
+use jupiter_amm_interface::{Amm, QuoteParams, SwapMode};
+use solana_sdk::pubkey::Pubkey;
+
+mod utils;
+use utils::*;
+
+const DECIMALS: [u8; 4] = [0, 6, 9, 18];
+
+// usdc reserve in ui tokens, small enough to fit in a u64 at 18 decimals
+const RESERVE_UI: u64 = 10;
+
+// fixture vault with usdc re-denominated to `decimals`, priced at $1 and held only in reserve
+fn init_amm_with_decimals(decimals: u8) -> CarrotAmm {
+    let mut carrot_amm = init_amm();
+
+    let asset = carrot_amm
+        .asset_state
+        .iter_mut()
+        .find(|a| a.mint.eq(&USDC_MINT))
+        .unwrap();
+    asset.mint_decimals = decimals;
+    asset.ata_amount = RESERVE_UI * 10_u64.pow(decimals.into());
+    asset.oracle_price_up = 100_000_000;
+    asset.oracle_price_down = 100_000_000;
+    asset.oracle_price_expo = -8;
+    let asset_id = asset.asset_id;
+
+    for strategy in carrot_amm.vault_state.strategies.iter_mut() {
+        if strategy.asset_id == asset_id {
+            strategy.balance = 0;
+            strategy.net_earnings = 0;
+        }
+    }
+    carrot_amm
+}
+
+fn quote(
+    carrot_amm: &CarrotAmm,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+    swap_mode: SwapMode,
+) -> (u64, u64) {
+    let quote = carrot_amm
+        .quote(&QuoteParams {
+            input_mint,
+            output_mint,
+            amount,
+            swap_mode,
+        })
+        .unwrap();
+    (quote.in_amount, quote.out_amount)
+}
+
+#[tokio::test]
+async fn test_issue_across_decimals() {
+    // the same ui deposit earns the same shares whatever the mint decimals
+    let shares: Vec<u64> = DECIMALS
+        .iter()
+        .map(|&decimals| {
+            let one_token = 10_u64.pow(decimals.into());
+            quote(
+                &init_amm_with_decimals(decimals),
+                USDC_MINT,
+                CRT_MINT,
+                one_token,
+                SwapMode::ExactIn,
+            )
+            .1
+        })
+        .collect();
+
+    assert!(shares[0] > 0);
+    assert!(shares.iter().all(|&s| s == shares[0]), "{:?}", shares);
+}
+
+#[tokio::test]
+async fn test_redeem_across_decimals() {
+    let shares = 5_000_000;
+    let out_amount = |decimals| {
+        quote(
+            &init_amm_with_decimals(decimals),
+            CRT_MINT,
+            USDC_MINT,
+            shares,
+            SwapMode::ExactIn,
+        )
+        .1
+    };
+
+    // usd is kept at 9 decimals, so fewer decimals round down and more scale up exactly
+    let nine = out_amount(9);
+    assert!(nine > 0);
+    assert_eq!(nine / 1_000_000_000, out_amount(0));
+    assert_eq!(nine / 1_000, out_amount(6));
+    assert_eq!(nine * 1_000_000_000, out_amount(18));
+}
+
+#[tokio::test]
+async fn test_exact_out_across_decimals() {
+    for decimals in DECIMALS {
+        let carrot_amm = init_amm_with_decimals(decimals);
+        let one_token = 10_u64.pow(decimals.into());
+
+        // shares for a ui token, and a ui token for shares
+        let (in_amount, out_amount) = quote(
+            &carrot_amm,
+            USDC_MINT,
+            CRT_MINT,
+            1_000_000,
+            SwapMode::ExactOut,
+        );
+        assert!(out_amount >= 1_000_000, "decimals: {}", decimals);
+        assert!(in_amount <= one_token, "decimals: {}", decimals);
+
+        let (in_amount, out_amount) = quote(
+            &carrot_amm,
+            CRT_MINT,
+            USDC_MINT,
+            one_token,
+            SwapMode::ExactOut,
+        );
+        assert!(out_amount >= one_token, "decimals: {}", decimals);
+        assert!(in_amount > 0, "decimals: {}", decimals);
+    }
+}
+
+#[tokio::test]
+async fn test_tvl_across_decimals() {
+    // the reserve is worth the same usd whatever its decimals
+    let tvls: Vec<u128> = DECIMALS
+        .iter()
+        .map(|&decimals| {
+            init_amm_with_decimals(decimals)
+                .share_price()
+                .unwrap()
+                .vault_tvl
+        })
+        .collect();
+    assert!(tvls.iter().all(|&tvl| tvl == tvls[0]), "{:?}", tvls);
+}
@@ -0,0 +1,219 @@
+use amm::constants::{CARROT_LOG_PROGRAM, CARROT_PROGRAM, CRT_MINT, CRT_VAULT, USDC_MINT};
+use amm::errors::CarrotAmmError;
+use amm::events::{parse_inner_instructions, CarrotEvent, RawEvent, RedeemEvent};
+use amm::{instructions, CarrotSwap};
+use solana_program_test::BanksClient;
+use solana_sdk::{
+    inner_instruction::InnerInstruction, instruction::CompiledInstruction, pubkey::Pubkey,
+    signature::Signer, transaction::Transaction,
+};
+use spl_token::id as token_program_id;
+
+mod utils;
+use utils::*;
+
+// simulates the transaction and decodes the events of the carrot log cpis it records
+async fn simulate_events(banks_client: &mut BanksClient, tx: &Transaction) -> Vec<CarrotEvent> {
+    let simulation = banks_client.simulate_transaction(tx.clone()).await.unwrap();
+    simulation.result.unwrap().unwrap();
+
+    let inner_instructions = simulation
+        .simulation_details
+        .unwrap()
+        .inner_instructions
+        .unwrap();
+
+    parse_inner_instructions(
+        &tx.message.account_keys,
+        &tx.message.instructions,
+        &inner_instructions,
+    )
+    .unwrap()
+}
+
+// inner instruction of program_id_index at stack_height
+fn inner(program_id_index: u8, data: Vec<u8>, stack_height: u8) -> InnerInstruction {
+    InnerInstruction {
+        instruction: CompiledInstruction::new_from_raw_parts(program_id_index, data, vec![]),
+        stack_height,
+    }
+}
+
+#[tokio::test]
+async fn test_issue_and_redeem_events() {
+    let TestEnv {
+        mut banks_client,
+        payer,
+        recent_blockhash,
+        mut account_map,
+        payer_shares_ata,
+        payer_input_mint_ata,
+    } = setup_test_env(USDC_MINT, token_program_id(), 1_000_000_000).await;
+
+    let mut carrot_amm = init_amm_with_accounts(&account_map);
+
+    let issue_ix = instructions::issue(
+        CarrotSwap {
+            vault: CRT_VAULT,
+            shares_mint: CRT_MINT,
+            source_mint: USDC_MINT,
+            destination_mint: CRT_MINT,
+            user_source: payer_input_mint_ata,
+            user_destination: payer_shares_ata,
+            user_transfer_authority: payer.pubkey(),
+            assets: carrot_amm.asset_state.clone(),
+        },
+        1_000_000_000,
+    )
+    .unwrap();
+    let issue_tx = Transaction::new_signed_with_payer(
+        &[issue_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+
+    let events = simulate_events(&mut banks_client, &issue_tx).await;
+    banks_client.process_transaction(issue_tx).await.unwrap();
+    let shares_amount = get_token_balance(&mut banks_client, payer_shares_ata).await;
+
+    let [CarrotEvent::Issue(issue_event)] = events.as_slice() else {
+        panic!("expected a single issue event, got {:?}", events);
+    };
+    assert_eq!(payer.pubkey(), issue_event.user);
+    assert_eq!(USDC_MINT, issue_event.asset_mint);
+    assert_eq!(1_000_000_000, issue_event.asset_amount);
+    assert_eq!(shares_amount, issue_event.shares_amount);
+    assert!(issue_event.tvl > 0);
+
+    account_map = refresh_account_map(&mut banks_client, &account_map).await;
+    carrot_amm = init_amm_with_accounts(&account_map);
+
+    let redeem_ix = instructions::redeem(
+        CarrotSwap {
+            vault: CRT_VAULT,
+            shares_mint: CRT_MINT,
+            source_mint: CRT_MINT,
+            destination_mint: USDC_MINT,
+            user_source: payer_shares_ata,
+            user_destination: payer_input_mint_ata,
+            user_transfer_authority: payer.pubkey(),
+            assets: carrot_amm.asset_state.clone(),
+        },
+        shares_amount,
+    )
+    .unwrap();
+    let redeem_tx = Transaction::new_signed_with_payer(
+        &[redeem_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        banks_client.get_latest_blockhash().await.unwrap(),
+    );
+
+    let events = simulate_events(&mut banks_client, &redeem_tx).await;
+    banks_client.process_transaction(redeem_tx).await.unwrap();
+    let asset_amount = get_token_balance(&mut banks_client, payer_input_mint_ata).await;
+
+    let [CarrotEvent::Redeem(redeem_event)] = events.as_slice() else {
+        panic!("expected a single redeem event, got {:?}", events);
+    };
+    let (_, redemption_fee) = carrot_amm
+        .vault_state
+        .fee
+        .calculate_redemption_fee(shares_amount)
+        .unwrap();
+    assert_eq!(payer.pubkey(), redeem_event.user);
+    assert_eq!(USDC_MINT, redeem_event.asset_mint);
+    assert_eq!(asset_amount, redeem_event.asset_amount);
+    assert_eq!(shares_amount, redeem_event.shares_amount);
+    assert_eq!(redemption_fee, redeem_event.redemption_fee);
+    assert!(redeem_event.tvl > 0);
+}
+
+#[tokio::test]
+async fn test_decode_event_errors() {
+    let err = CarrotEvent::decode(&[5; 128]).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::UnknownEventDiscriminator),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+
+    // every truncation errors instead of panicking
+    let mut data = vec![RedeemEvent::VARIANT];
+    data.extend_from_slice(&[0; 32 + 32 + 8 + 8 + 8 + 8 + 16]);
+    CarrotEvent::decode(&data).unwrap();
+    for len in 0..data.len() {
+        let err = CarrotEvent::decode(&data[..len]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CarrotAmmError>(),
+            Some(CarrotAmmError::AccountDataTooShort { .. })
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_decode_raw_events() {
+    for variant in RawEvent::VARIANTS {
+        assert_eq!(
+            CarrotEvent::Raw(RawEvent {
+                variant,
+                data: vec![1, 2, 3],
+            }),
+            CarrotEvent::decode(&[variant, 1, 2, 3]).unwrap()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_parse_inner_instructions() {
+    let other_program = Pubkey::new_unique();
+    let account_keys = [CARROT_PROGRAM, CARROT_LOG_PROGRAM, CRT_VAULT, other_program];
+    let carrot_ix = CompiledInstruction::new_from_raw_parts(0, vec![], vec![2]);
+    let other_ix = CompiledInstruction::new_from_raw_parts(3, vec![], vec![]);
+    let raw_event = |variant: u8| {
+        CarrotEvent::Raw(RawEvent {
+            variant,
+            data: vec![variant; 8],
+        })
+    };
+
+    let instructions = [carrot_ix.clone(), other_ix.clone()];
+    let inner_instructions = [
+        vec![
+            // strategy deposit event logged by the carrot program
+            inner(1, vec![2; 9], 2),
+            // a cpi of the carrot program that logs an event itself is not a carrot event
+            inner(3, vec![], 2),
+            inner(1, vec![3; 9], 3),
+            // back at the carrot program
+            inner(1, vec![4; 9], 2),
+        ],
+        // another program logging to the carrot log program
+        vec![inner(1, vec![2; 9], 2)],
+    ];
+    assert_eq!(
+        vec![raw_event(2), raw_event(4)],
+        parse_inner_instructions(&account_keys, &instructions, &inner_instructions).unwrap()
+    );
+
+    // an unknown or malformed event of the carrot program is an error
+    for data in [vec![5; 9], vec![0; 4]] {
+        let inner_instructions = [vec![inner(1, data, 2)]];
+        assert!(
+            parse_inner_instructions(&account_keys, &[carrot_ix.clone()], &inner_instructions)
+                .is_err()
+        );
+    }
+
+    // stack heights must follow the invocation stack
+    for stack_height in [0, 1, 3] {
+        let inner_instructions = [vec![inner(1, vec![2; 9], stack_height)]];
+        let err =
+            parse_inner_instructions(&account_keys, &[carrot_ix.clone()], &inner_instructions)
+                .unwrap_err();
+        assert_eq!(
+            Some(&CarrotAmmError::InvalidInstructionStack),
+            err.downcast_ref::<CarrotAmmError>()
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use amm::constants::{CRT_MINT, CRT_VAULT, PYUSD_ORACLE, USDC_MINT, USDC_ORACLE, USDT_ORACLE};
+use amm::state::{PriceUpdateV2, MAX_AGE};
+use amm::{state::Vault, CarrotAmm};
+use jupiter_amm_interface::{AccountMap, Amm, ClockRef, QuoteParams, SwapMode};
+use std::sync::atomic::Ordering;
+
+mod utils;
+use utils::*;
+
+// offsets of the management fee fields inside the vault account
+const VAULT_MANAGEMENT_FEE_BPS_OFFSET: usize = 8 + 32 + 32 + 2 + 8;
+
+const SECONDS_IN_YEAR: i64 = 31557600;
+
+fn clock_ref_at(unix_timestamp: i64) -> ClockRef {
+    let clock_ref = ClockRef::default();
+    clock_ref
+        .unix_timestamp
+        .store(unix_timestamp, Ordering::Relaxed);
+    clock_ref
+}
+
+fn publish_times(account_map: &AccountMap) -> Vec<i64> {
+    [USDC_ORACLE, USDT_ORACLE, PYUSD_ORACLE]
+        .iter()
+        .map(|oracle| {
+            PriceUpdateV2::load(&account_map.get(oracle).unwrap().data)
+                .unwrap()
+                .price_message
+                .publish_time
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_oracle_age_uses_clock() {
+    let account_map = load_account_map_from_file();
+    let publish_times = publish_times(&account_map);
+    let oldest = *publish_times.iter().min().unwrap();
+    let newest = *publish_times.iter().max().unwrap();
+
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, MAX_AGE);
+
+    // every oracle is fresh relative to the clock
+    carrot_amm.clock_ref = Some(clock_ref_at(newest + 10));
+    assert_eq!(newest + 10, carrot_amm.current_timestamp());
+    carrot_amm.update(&account_map).unwrap();
+
+    // oldest oracle is just past the max age
+    carrot_amm.clock_ref = Some(clock_ref_at(oldest + MAX_AGE as i64 + 1));
+    let update_result = carrot_amm.update(&account_map);
+    assert_eq!(
+        "Oracle Price is Stale",
+        update_result.unwrap_err().to_string()
+    );
+}
+
+#[tokio::test]
+async fn test_management_fee_uses_clock() {
+    let mut account_map = load_account_map_from_file();
+
+    // charge a 1% management fee
+    account_map.get_mut(&CRT_VAULT).unwrap().data
+        [VAULT_MANAGEMENT_FEE_BPS_OFFSET..VAULT_MANAGEMENT_FEE_BPS_OFFSET + 2]
+        .copy_from_slice(&100_u16.to_le_bytes());
+
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    let last_update = vault_state.fee.management_fee_last_update;
+    assert_eq!(100, vault_state.fee.management_fee_bps);
+
+    let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+    carrot_amm.update(&account_map).unwrap();
+
+    let quote_params = QuoteParams {
+        input_mint: USDC_MINT,
+        output_mint: CRT_MINT,
+        amount: 1_000_000_000,
+        swap_mode: SwapMode::ExactIn,
+    };
+
+    // no fee accrues within a minute of the last update
+    carrot_amm.clock_ref = Some(clock_ref_at(last_update + 30));
+    let quote_without_fee = carrot_amm.quote(&quote_params).unwrap();
+    assert_eq!(9684056623, quote_without_fee.out_amount);
+
+    // a year of fees inflates the supply, so the same deposit earns more shares
+    carrot_amm.clock_ref = Some(clock_ref_at(last_update + SECONDS_IN_YEAR));
+    let quote_with_fee = carrot_amm.quote(&quote_params).unwrap();
+    assert!(quote_with_fee.out_amount > quote_without_fee.out_amount);
+
+    // quotes are deterministic for a given clock
+    let quote_again = carrot_amm.quote(&quote_params).unwrap();
+    assert_eq!(quote_with_fee.out_amount, quote_again.out_amount);
+}
@@ -0,0 +1,307 @@
+use amm::constants::{
+    CRT_MINT, CRT_VAULT, PYUSD_ORACLE, PYUSD_VAULT_ATA, USDC_ORACLE, USDC_VAULT_ATA, USDT_ORACLE,
+    USDT_VAULT_ATA,
+};
+use amm::errors::CarrotAmmError;
+use amm::{instructions, state::Vault, CarrotAmm};
+use anyhow::Result;
+use jupiter_amm_interface::{AccountMap, Amm, ClockRef, Quote, QuoteParams, SwapMode};
+use proptest::prelude::*;
+use solana_program_test::BanksClient;
+use solana_sdk::{
+    clock::Clock,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+mod utils;
+use utils::*;
+
+// offsets of the price fields inside a fully verified price update account
+const PRICE_OFFSET: usize = 8 + 32 + 1 + 32;
+const EMA_PRICE_OFFSET: usize = PRICE_OFFSET + 8 + 8 + 4 + 8 + 8;
+
+// offset of the amount in a token account and of the supply in a mint
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 32 + 32;
+const MINT_SUPPLY_OFFSET: usize = 4 + 32;
+
+// every case starts a program test, keep the suite fast enough to run with the others
+const CASES: u32 = 32;
+
+// vault state and user amounts generated for one case
+#[derive(Debug)]
+struct Case {
+    // index into fixture_input_mints
+    asset: usize,
+    // oracle price and ema price of the asset, in bps of the fixture price
+    price_bps: u64,
+    ema_price_bps: u64,
+    // vault reserve of the asset, in bps of the fixture reserve
+    reserve_bps: u64,
+    // shares supply, in bps of the fixture supply
+    supply_bps: u64,
+    deposit: u64,
+    // shares redeemed, in bps of the shares minted by the deposit
+    redeem_bps: u64,
+    // exact out cases ask for the output the exact in amounts would quote
+    swap_mode: SwapMode,
+    // transfer fee of the pyusd mint, only used by pyusd cases
+    transfer_fee_bps: u16,
+}
+
+fn case_strategy() -> impl Strategy<Value = Case> {
+    (
+        0..fixture_input_mints().len(),
+        9_000_u64..=11_000,
+        9_000_u64..=11_000,
+        0_u64..=20_000,
+        5_000_u64..=20_000,
+        1_000_u64..=1_000_000_000_000,
+        1_u64..=10_000,
+        prop_oneof![Just(SwapMode::ExactIn), Just(SwapMode::ExactOut)],
+        0_u16..=500,
+    )
+        .prop_map(
+            |(
+                asset,
+                price_bps,
+                ema_price_bps,
+                reserve_bps,
+                supply_bps,
+                deposit,
+                redeem_bps,
+                swap_mode,
+                transfer_fee_bps,
+            )| Case {
+                asset,
+                price_bps,
+                ema_price_bps,
+                reserve_bps,
+                supply_bps,
+                deposit,
+                redeem_bps,
+                swap_mode,
+                transfer_fee_bps,
+            },
+        )
+}
+
+fn scale_u64(data: &mut [u8], offset: usize, bps: u64) {
+    let value = u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    let scaled = (value as u128 * bps as u128 / 10_000) as u64;
+    data[offset..offset + 8].copy_from_slice(&scaled.to_le_bytes());
+}
+
+fn scale_i64(data: &mut [u8], offset: usize, bps: u64) {
+    let value = i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    let scaled = (value as i128 * bps as i128 / 10_000) as i64;
+    data[offset..offset + 8].copy_from_slice(&scaled.to_le_bytes());
+}
+
+// fixture accounts rewritten to the generated vault state
+fn case_account_map(case: &Case) -> AccountMap {
+    let (oracle, vault_ata) = [
+        (USDC_ORACLE, USDC_VAULT_ATA),
+        (USDT_ORACLE, USDT_VAULT_ATA),
+        (PYUSD_ORACLE, PYUSD_VAULT_ATA),
+    ][case.asset];
+
+    let mut account_map = load_account_map_from_file();
+
+    let data = &mut account_map.get_mut(&oracle).unwrap().data;
+    scale_i64(data, PRICE_OFFSET, case.price_bps);
+    scale_i64(data, EMA_PRICE_OFFSET, case.ema_price_bps);
+
+    let data = &mut account_map.get_mut(&vault_ata).unwrap().data;
+    scale_u64(data, TOKEN_ACCOUNT_AMOUNT_OFFSET, case.reserve_bps);
+
+    let data = &mut account_map.get_mut(&CRT_MINT).unwrap().data;
+    scale_u64(data, MINT_SUPPLY_OFFSET, case.supply_bps);
+
+    // the same fee before and after the change, so it does not depend on the bank epoch
+    set_pyusd_transfer_fee(
+        &mut account_map,
+        case.transfer_fee_bps,
+        case.transfer_fee_bps,
+        0,
+    );
+
+    account_map
+}
+
+// amm loaded from the bank accounts and priced at the bank clock
+async fn load_amm(banks_client: &mut BanksClient, account_map: &AccountMap) -> CarrotAmm {
+    let account_map = refresh_account_map(banks_client, account_map).await;
+    let clock: Clock = banks_client.get_sysvar().await.unwrap();
+
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+    carrot_amm.clock_ref = Some(ClockRef::from(clock));
+    carrot_amm.update(&account_map).unwrap();
+    carrot_amm
+}
+
+// exact in quote, used to pick the output of the exact out cases
+fn quote_exact_in(
+    carrot_amm: &CarrotAmm,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+) -> Result<Quote> {
+    carrot_amm.quote(&QuoteParams {
+        input_mint,
+        output_mint,
+        amount,
+        swap_mode: SwapMode::ExactIn,
+    })
+}
+
+async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    ix: Instruction,
+) -> Result<(), TransactionError> {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash,
+    );
+
+    banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap()
+        .result
+}
+
+// (quoted, received) for the issue and then the redeem of the case
+// the redeem is none when it exceeds the vault liquidity and both the quote and the program reject it
+async fn run_case(case: &Case) -> ((u64, u64), Option<(u64, u64)>) {
+    let (input_mint, token_program) = fixture_input_mints()[case.asset];
+    let TestEnv {
+        mut banks_client,
+        payer,
+        account_map,
+        payer_shares_ata,
+        payer_input_mint_ata,
+        ..
+    } = setup_test_env_with_accounts(
+        case_account_map(case),
+        input_mint,
+        token_program,
+        case.deposit,
+    )
+    .await;
+
+    // issue
+    let carrot_amm = load_amm(&mut banks_client, &account_map).await;
+    let issue_amount = match case.swap_mode {
+        SwapMode::ExactIn => case.deposit,
+        SwapMode::ExactOut => {
+            quote_exact_in(&carrot_amm, input_mint, CRT_MINT, case.deposit)
+                .unwrap()
+                .out_amount
+        }
+    };
+    let issue_quote = carrot_amm
+        .quote(&QuoteParams {
+            input_mint,
+            output_mint: CRT_MINT,
+            amount: issue_amount,
+            swap_mode: case.swap_mode,
+        })
+        .unwrap();
+    let swap = carrot_amm.carrot_swap(
+        input_mint,
+        CRT_MINT,
+        payer_input_mint_ata,
+        payer_shares_ata,
+        payer.pubkey(),
+    );
+    process(
+        &mut banks_client,
+        &payer,
+        instructions::issue(swap, issue_quote.in_amount).unwrap(),
+    )
+    .await
+    .unwrap();
+    let shares = get_token_balance(&mut banks_client, payer_shares_ata).await;
+    let issue = (issue_quote.out_amount, shares);
+
+    // redeem part of the minted shares back into the same asset
+    let shares_amount = ((shares as u128 * case.redeem_bps as u128 / 10_000) as u64).max(1);
+    let input_balance = get_token_balance(&mut banks_client, payer_input_mint_ata).await;
+    let carrot_amm = load_amm(&mut banks_client, &account_map).await;
+    let redeem_quote = match case.swap_mode {
+        SwapMode::ExactIn => Ok(shares_amount),
+        SwapMode::ExactOut => quote_exact_in(&carrot_amm, CRT_MINT, input_mint, shares_amount)
+            .map(|quote| quote.out_amount.max(1)),
+    }
+    .and_then(|amount| {
+        carrot_amm.quote(&QuoteParams {
+            input_mint: CRT_MINT,
+            output_mint: input_mint,
+            amount,
+            swap_mode: case.swap_mode,
+        })
+    });
+    let swap = carrot_amm.carrot_swap(
+        CRT_MINT,
+        input_mint,
+        payer_shares_ata,
+        payer_input_mint_ata,
+        payer.pubkey(),
+    );
+
+    let redeem_quote = match redeem_quote {
+        Ok(redeem_quote) => redeem_quote,
+        Err(err) => {
+            // the vault holds less of the asset than the shares are worth
+            assert_eq!(
+                Some(&CarrotAmmError::InsufficientLiquidity),
+                err.downcast_ref::<CarrotAmmError>()
+            );
+            let result = process(
+                &mut banks_client,
+                &payer,
+                instructions::redeem(swap, shares_amount).unwrap(),
+            )
+            .await;
+            assert!(result.is_err(), "program redeemed more than the liquidity");
+            return (issue, None);
+        }
+    };
+    process(
+        &mut banks_client,
+        &payer,
+        instructions::redeem(swap, redeem_quote.in_amount).unwrap(),
+    )
+    .await
+    .unwrap();
+    let redeemed = get_token_balance(&mut banks_client, payer_input_mint_ata).await - input_balance;
+
+    (issue, Some((redeem_quote.out_amount, redeemed)))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    // quotes must match what the program mints and pays out to the unit
+    #[test]
+    fn test_quotes_match_program(case in case_strategy()) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let ((issue_quoted, issued), redeem) = runtime.block_on(run_case(&case));
+
+        prop_assert_eq!(issue_quoted, issued, "issue");
+        if let Some((redeem_quoted, redeemed)) = redeem {
+            prop_assert_eq!(redeem_quoted, redeemed, "redeem");
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use amm::constants::{CRT_MINT, CRT_VAULT, PYUSD_MINT, USDC_MINT, USDT_MINT};
+use amm::errors::CarrotAmmError;
+use amm::{
+    instructions,
+    state::{Asset, Fee, StrategyRecord, Vault},
+    CarrotAmm,
+};
+use jupiter_amm_interface::{AccountMap, Amm, QuoteParams, SwapMode};
+use proptest::prelude::*;
+use solana_sdk::{pubkey::Pubkey, signature::Signer, transaction::Transaction};
+use spl_token::id as token_program_id;
+
+mod utils;
+use utils::*;
+
+const ASSET_MINTS: [Pubkey; 3] = [USDC_MINT, USDT_MINT, PYUSD_MINT];
+
+// offset of the fee inside the vault account, after discriminator, authority and shares mint
+const VAULT_FEE_OFFSET: usize = 8 + 32 + 32;
+
+// offset of the net earnings inside a strategy record, after strategy id, asset id and balance
+const STRATEGY_NET_EARNINGS_OFFSET: usize = 2 + 2 + 8;
+
+// offset of the supply in a mint
+const MINT_SUPPLY_OFFSET: usize = 4 + 32;
+
+// fixture vault with fees switched off so the adjusted supply is the mint supply
+// fee shares are priced with shares_earned too, so an empty vault would bootstrap them
+fn init_fee_free_amm() -> CarrotAmm {
+    let mut carrot_amm = init_amm();
+
+    let fee = &mut carrot_amm.vault_state.fee;
+    fee.management_fee_bps = 0;
+    fee.management_fee_accumulated = 0;
+    fee.performance_fee_bps = 0;
+    fee.redemption_fee_accumulated = 0;
+    for strategy in carrot_amm.vault_state.strategies.iter_mut() {
+        strategy.net_earnings = 0;
+    }
+    carrot_amm
+}
+
+fn with_zero_tvl(mut carrot_amm: CarrotAmm) -> CarrotAmm {
+    for asset in carrot_amm.asset_state.iter_mut() {
+        asset.ata_amount = 0;
+    }
+    for strategy in carrot_amm.vault_state.strategies.iter_mut() {
+        strategy.balance = 0;
+    }
+    carrot_amm
+}
+
+fn with_zero_supply(mut carrot_amm: CarrotAmm) -> CarrotAmm {
+    carrot_amm.shares_state.as_mut().unwrap().supply = 0;
+    carrot_amm
+}
+
+// fixture accounts of a fee free vault with no shares minted, for the program
+fn empty_vault_account_map() -> AccountMap {
+    let mut account_map = load_account_map_from_file();
+
+    let data = &mut account_map.get_mut(&CRT_VAULT).unwrap().data;
+    data[VAULT_FEE_OFFSET..VAULT_FEE_OFFSET + Fee::SPACE].fill(0);
+
+    // without earnings there is no performance fee to bootstrap either
+    let vault = Vault::load(data).unwrap();
+    let strategies_offset =
+        VAULT_FEE_OFFSET + Fee::SPACE + 1 + 2 + 2 + 4 + vault.assets.len() * Asset::SPACE + 4;
+    for index in 0..vault.strategies.len() {
+        let offset =
+            strategies_offset + index * StrategyRecord::SPACE + STRATEGY_NET_EARNINGS_OFFSET;
+        data[offset..offset + 8].fill(0);
+    }
+
+    let data = &mut account_map.get_mut(&CRT_MINT).unwrap().data;
+    data[MINT_SUPPLY_OFFSET..MINT_SUPPLY_OFFSET + 8].fill(0);
+
+    account_map
+}
+
+fn issue(carrot_amm: &CarrotAmm, input_mint: Pubkey, amount: u64) -> anyhow::Result<u64> {
+    Ok(carrot_amm
+        .quote(&QuoteParams {
+            input_mint,
+            output_mint: CRT_MINT,
+            amount,
+            swap_mode: SwapMode::ExactIn,
+        })?
+        .out_amount)
+}
+
+fn bootstrap_shares(carrot_amm: &CarrotAmm) -> u64 {
+    100 * 10_u64.pow(carrot_amm.shares_state.unwrap().decimals as u32)
+}
+
+proptest! {
+    // the deposit size is ignored
+    #[test]
+    fn test_zero_tvl_mints_bootstrap_shares(asset in 0..ASSET_MINTS.len(), amount in 1_u64..) {
+        let carrot_amm = with_zero_tvl(init_fee_free_amm());
+        prop_assert_eq!(0, carrot_amm.share_price().unwrap().vault_tvl);
+
+        let shares = issue(&carrot_amm, ASSET_MINTS[asset], amount).unwrap();
+        prop_assert_eq!(bootstrap_shares(&carrot_amm), shares);
+    }
+
+    #[test]
+    fn test_zero_supply_mints_bootstrap_shares(asset in 0..ASSET_MINTS.len(), amount in 1_u64..) {
+        let carrot_amm = with_zero_supply(init_fee_free_amm());
+        prop_assert_ne!(0, carrot_amm.share_price().unwrap().vault_tvl);
+        let bootstrap = bootstrap_shares(&carrot_amm);
+
+        prop_assert_eq!(bootstrap, issue(&carrot_amm, ASSET_MINTS[asset], amount).unwrap());
+
+        // both empty behaves the same
+        let carrot_amm = with_zero_tvl(carrot_amm);
+        prop_assert_eq!(bootstrap, issue(&carrot_amm, ASSET_MINTS[asset], amount).unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_bootstrap_shares_decimals() {
+    let mut carrot_amm = with_zero_tvl(init_fee_free_amm());
+
+    // 100 ui shares
+    for (decimals, expected) in [
+        (0, 100),
+        (6, 100_000_000),
+        (9, 100_000_000_000),
+        (17, 10_000_000_000_000_000_000),
+    ] {
+        carrot_amm.shares_state.as_mut().unwrap().decimals = decimals;
+        assert_eq!(expected, issue(&carrot_amm, USDC_MINT, 1).unwrap());
+    }
+
+    // errors instead of saturating once that no longer fits in a u64
+    for decimals in [18, u8::MAX] {
+        carrot_amm.shares_state.as_mut().unwrap().decimals = decimals;
+        let err = issue(&carrot_amm, USDC_MINT, 1).unwrap_err();
+        assert_eq!(
+            Some(&CarrotAmmError::MathOverflow),
+            err.downcast_ref::<CarrotAmmError>()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_tiny_deposits() {
+    let carrot_amm = init_fee_free_amm();
+
+    // a funded vault prices tiny deposits pro rata and rounds down, never bootstraps
+    let mut previous = 0;
+    for amount in 1..1_000 {
+        let shares = issue(&carrot_amm, USDC_MINT, amount).unwrap();
+        assert!(shares >= previous);
+        assert!(shares <= amount * 10);
+        previous = shares;
+    }
+
+    // an empty vault mints the same shares for a single unit as for any other deposit
+    let empty_amm = with_zero_supply(init_fee_free_amm());
+    assert_eq!(
+        issue(&empty_amm, USDC_MINT, 1).unwrap(),
+        issue(&empty_amm, USDC_MINT, 1_000_000_000_000).unwrap()
+    );
+
+    // zero deposits are rejected rather than quoted the bootstrap shares
+    for carrot_amm in [carrot_amm, empty_amm] {
+        for swap_mode in [SwapMode::ExactIn, SwapMode::ExactOut] {
+            let err = carrot_amm
+                .quote(&QuoteParams {
+                    input_mint: USDC_MINT,
+                    output_mint: CRT_MINT,
+                    amount: 0,
+                    swap_mode,
+                })
+                .unwrap_err();
+            assert_eq!(
+                Some(&CarrotAmmError::ZeroDepositAmount),
+                err.downcast_ref::<CarrotAmmError>()
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_empty_vault_exact_out() {
+    let carrot_amm = with_zero_tvl(init_fee_free_amm());
+
+    // no minimum deposit exists when any deposit earns the bootstrap shares
+    let quote_result = carrot_amm.quote(&QuoteParams {
+        input_mint: USDC_MINT,
+        output_mint: CRT_MINT,
+        amount: 1_000_000,
+        swap_mode: SwapMode::ExactOut,
+    });
+    assert_eq!(
+        Some(&CarrotAmmError::DivisionByZero),
+        quote_result.unwrap_err().downcast_ref::<CarrotAmmError>()
+    );
+}
+
+#[tokio::test]
+async fn test_empty_vault_bootstrap_matches_program() {
+    let account_map = empty_vault_account_map();
+    let carrot_amm = init_amm_with_accounts(&account_map);
+    let quoted = issue(&carrot_amm, USDC_MINT, 1_000_000).unwrap();
+    assert_eq!(bootstrap_shares(&carrot_amm), quoted);
+
+    let TestEnv {
+        mut banks_client,
+        payer,
+        recent_blockhash,
+        payer_shares_ata,
+        payer_input_mint_ata,
+        ..
+    } = setup_test_env_with_accounts(account_map, USDC_MINT, token_program_id(), 1_000_000).await;
+
+    let swap = carrot_amm.carrot_swap(
+        USDC_MINT,
+        CRT_MINT,
+        payer_input_mint_ata,
+        payer_shares_ata,
+        payer.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[instructions::issue(swap, 1_000_000).unwrap()],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // the program mints the same flat amount
+    assert_eq!(
+        quoted,
+        get_token_balance(&mut banks_client, payer_shares_ata).await
+    );
+}
@@ -0,0 +1,64 @@
+use amm::constants::{CARROT_PROGRAM, CRT_MINT, USDC_MINT};
+use amm::errors::CarrotAmmError;
+use amm::instructions::{self, ISSUE_DISCRIMINATOR, REDEEM_DISCRIMINATOR};
+use solana_sdk::{hash::hash, instruction::AccountMeta, pubkey::Pubkey};
+
+mod utils;
+use utils::*;
+
+const USER_SHARES: Pubkey = Pubkey::new_from_array([1; 32]);
+const USER_USDC: Pubkey = Pubkey::new_from_array([2; 32]);
+const AUTHORITY: Pubkey = Pubkey::new_from_array([3; 32]);
+
+#[tokio::test]
+async fn test_instruction_data() {
+    assert_eq!(hash(b"global:issue").to_bytes()[..8], ISSUE_DISCRIMINATOR);
+    assert_eq!(hash(b"global:redeem").to_bytes()[..8], REDEEM_DISCRIMINATOR);
+
+    let data = instructions::issue_data(1_000_000_000);
+    assert_eq!(ISSUE_DISCRIMINATOR, data[..8]);
+    assert_eq!(1_000_000_000_u64.to_le_bytes(), data[8..]);
+
+    let data = instructions::redeem_data(u64::MAX);
+    assert_eq!(REDEEM_DISCRIMINATOR, data[..8]);
+    assert_eq!(u64::MAX.to_le_bytes(), data[8..]);
+}
+
+#[tokio::test]
+async fn test_issue_instruction() {
+    let carrot_amm = init_amm();
+    let carrot_swap =
+        carrot_amm.carrot_swap(USDC_MINT, CRT_MINT, USER_USDC, USER_SHARES, AUTHORITY);
+
+    let ix = instructions::issue(carrot_swap.clone(), 1_000_000).unwrap();
+    let accounts: Vec<AccountMeta> = carrot_swap.clone().try_into().unwrap();
+    assert_eq!(CARROT_PROGRAM, ix.program_id);
+    assert_eq!(accounts, ix.accounts);
+    assert_eq!(instructions::issue_data(1_000_000), ix.data);
+
+    // issue spends an asset, not shares
+    let err = instructions::redeem(carrot_swap, 1_000_000).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::InvalidSwapDirection),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
+
+#[tokio::test]
+async fn test_redeem_instruction() {
+    let carrot_amm = init_amm();
+    let carrot_swap =
+        carrot_amm.carrot_swap(CRT_MINT, USDC_MINT, USER_SHARES, USER_USDC, AUTHORITY);
+
+    let ix = instructions::redeem(carrot_swap.clone(), 1_000).unwrap();
+    let accounts: Vec<AccountMeta> = carrot_swap.clone().try_into().unwrap();
+    assert_eq!(CARROT_PROGRAM, ix.program_id);
+    assert_eq!(accounts, ix.accounts);
+    assert_eq!(instructions::redeem_data(1_000), ix.data);
+
+    let err = instructions::issue(carrot_swap, 1_000).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::InvalidSwapDirection),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
@@ -182,11 +182,14 @@ async fn test_issue_and_redeem() {
         let issue_data = get_ix_data("issue", input_mint_amount);
 
         let carrot_swap_issue = CarrotSwap {
+            vault: CRT_VAULT,
+            shares_mint: CRT_MINT,
             source_mint: issue_quote_params.input_mint,
             destination_mint: CRT_MINT,
             user_source: payer_input_mint_ata.pubkey(),
             user_destination: payer_shares_ata.pubkey(),
             user_transfer_authority: payer.pubkey(),
+            assets: carrot_amm.asset_state.clone(),
         };
 
         let issue_accounts: Vec<AccountMeta> = carrot_swap_issue.try_into().unwrap();
@@ -251,11 +254,14 @@ async fn test_issue_and_redeem() {
         let redeem_data = get_ix_data("redeem", crt_amount);
 
         let carrot_swap_redeem = CarrotSwap {
+            vault: CRT_VAULT,
+            shares_mint: CRT_MINT,
             source_mint: redeem_quote_params.input_mint,
             destination_mint: issue_quote_params.input_mint, // the mint that was issue is what we wanna get back for this test
             user_source: payer_shares_ata.pubkey(),
             user_destination: payer_input_mint_ata.pubkey(),
             user_transfer_authority: payer.pubkey(),
+            assets: carrot_amm.asset_state.clone(),
         };
 
         let redeem_accounts: Vec<AccountMeta> = carrot_swap_redeem.try_into().unwrap();
@@ -0,0 +1,148 @@
+use amm::{
+    constants::{CRT_MINT, CRT_VAULT},
+    instructions,
+    state::Vault,
+    CarrotAmm, CarrotSwap,
+};
+use jupiter_amm_interface::{Amm, QuoteParams, SwapMode};
+use solana_sdk::{signature::Signer, transaction::Transaction};
+
+mod utils;
+use utils::*;
+
+#[tokio::test]
+async fn test_issue_and_redeem_exact_out() {
+    for (input_mint, token_program) in fixture_input_mints().into_iter() {
+        let TestEnv {
+            mut banks_client,
+            payer,
+            recent_blockhash,
+            mut account_map,
+            payer_shares_ata,
+            payer_input_mint_ata,
+        } = setup_test_env(input_mint, token_program, 1_000_000_000).await;
+
+        let vault_account = account_map.get(&CRT_VAULT).unwrap();
+        let vault_state = Vault::load(&vault_account.data).unwrap();
+
+        // init amm
+        let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+
+        // update account cache
+        carrot_amm.update(&account_map).unwrap();
+
+        // quote the input required to receive exactly 1 CRT
+        let shares_amount = 1_000_000_000;
+
+        let issue_quote = carrot_amm
+            .quote(&QuoteParams {
+                input_mint,
+                output_mint: CRT_MINT,
+                amount: shares_amount,
+                swap_mode: SwapMode::ExactOut,
+            })
+            .unwrap();
+
+        let issue_ix = instructions::issue(
+            CarrotSwap {
+                vault: CRT_VAULT,
+                shares_mint: CRT_MINT,
+                source_mint: input_mint,
+                destination_mint: CRT_MINT,
+                user_source: payer_input_mint_ata,
+                user_destination: payer_shares_ata,
+                user_transfer_authority: payer.pubkey(),
+                assets: carrot_amm.asset_state.clone(),
+            },
+            issue_quote.in_amount,
+        )
+        .unwrap();
+
+        let issue_tx = Transaction::new_signed_with_payer(
+            &[issue_ix],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+
+        banks_client
+            .process_transaction_with_metadata(issue_tx)
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+
+        // the program must mint at least the requested shares and exactly what was quoted
+        let payer_shares_amount = get_token_balance(&mut banks_client, payer_shares_ata).await;
+        assert!(
+            payer_shares_amount >= shares_amount,
+            "input_mint: {}",
+            input_mint
+        );
+        assert_eq!(
+            issue_quote.out_amount, payer_shares_amount,
+            "input_mint: {}",
+            input_mint
+        );
+
+        // fetch updated accounts as result of issue tx
+        account_map = refresh_account_map(&mut banks_client, &account_map).await;
+
+        // update amm with new account data
+        carrot_amm.update(&account_map).unwrap();
+
+        // quote the shares required to receive exactly 50 units of the asset
+        let asset_amount = 50_000_000;
+        let payer_input_mint_before =
+            get_token_balance(&mut banks_client, payer_input_mint_ata).await;
+
+        let redeem_quote = carrot_amm
+            .quote(&QuoteParams {
+                input_mint: CRT_MINT,
+                output_mint: input_mint,
+                amount: asset_amount,
+                swap_mode: SwapMode::ExactOut,
+            })
+            .unwrap();
+        assert!(redeem_quote.in_amount <= payer_shares_amount);
+
+        let redeem_ix = instructions::redeem(
+            CarrotSwap {
+                vault: CRT_VAULT,
+                shares_mint: CRT_MINT,
+                source_mint: CRT_MINT,
+                destination_mint: input_mint,
+                user_source: payer_shares_ata,
+                user_destination: payer_input_mint_ata,
+                user_transfer_authority: payer.pubkey(),
+                assets: carrot_amm.asset_state.clone(),
+            },
+            redeem_quote.in_amount,
+        )
+        .unwrap();
+
+        let redeem_tx = Transaction::new_signed_with_payer(
+            &[redeem_ix],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+
+        banks_client
+            .process_transaction_with_metadata(redeem_tx)
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+
+        // the program must pay out at least the requested amount and exactly what was quoted
+        let received = get_token_balance(&mut banks_client, payer_input_mint_ata).await
+            - payer_input_mint_before;
+        assert!(received >= asset_amount, "input_mint: {}", input_mint);
+        assert_eq!(
+            redeem_quote.out_amount, received,
+            "input_mint: {}",
+            input_mint
+        );
+    }
+}
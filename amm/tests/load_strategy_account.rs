@@ -0,0 +1,193 @@
+use amm::constants::{CARROT_PROGRAM, CRT_VAULT, USDC_MINT};
+use amm::errors::CarrotAmmError;
+use amm::state::{Strategy, StrategyType};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+// encodes a strategy account the way the carrot program stores it
+// the variant field counts match the program's strategy type deserializer
+fn encode_strategy(name: &str, strategy_id: u16, variant: u8, fields: &[u8]) -> Vec<u8> {
+    let mut data = Strategy::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&(name.len() as u32).to_le_bytes());
+    data.extend_from_slice(name.as_bytes());
+    data.extend_from_slice(&strategy_id.to_le_bytes());
+    data.extend_from_slice(USDC_MINT.as_ref());
+    data.extend_from_slice(CRT_VAULT.as_ref());
+    data.push(variant);
+    data.extend_from_slice(fields);
+    data
+}
+
+fn strategy_account(data: Vec<u8>) -> Account {
+    Account {
+        data,
+        owner: CARROT_PROGRAM,
+        ..Account::default()
+    }
+}
+
+fn load(data: &[u8]) -> anyhow::Result<Strategy> {
+    Strategy::load(&Pubkey::new_unique(), &strategy_account(data.to_vec()))
+}
+
+fn keys(n: u8) -> Vec<Pubkey> {
+    (1..=n).map(|i| Pubkey::new_from_array([i; 32])).collect()
+}
+
+fn encode_fields(keys: &[Pubkey], tail: &[u16]) -> Vec<u8> {
+    let mut data: Vec<u8> = keys.iter().flat_map(|k| k.to_bytes()).collect();
+    for value in tail {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data
+}
+
+#[tokio::test]
+async fn test_load_strategy_account() {
+    let k = keys(8);
+    let cases = [
+        (
+            0,
+            encode_fields(&k[..6], &[]),
+            StrategyType::MarginfiSupply {
+                account: k[0],
+                group: k[1],
+                bank: k[2],
+                bank_liquidity_vault: k[3],
+                bank_liquidity_vault_authority: k[4],
+                oracle: k[5],
+            },
+        ),
+        (
+            1,
+            encode_fields(&k[..8], &[]),
+            StrategyType::KlendSupply {
+                reserve: k[0],
+                reserve_collateral_mint: k[1],
+                reserve_liquidity_supply: k[2],
+                reserve_destination_deposit_collateral: k[3],
+                reserve_farm_state: k[4],
+                lending_market: k[5],
+                oracle: k[6],
+                scope_prices: k[7],
+            },
+        ),
+        (
+            2,
+            encode_fields(&k[..8], &[]),
+            StrategyType::SolendSupply {
+                reserve: k[0],
+                reserve_collateral_mint: k[1],
+                reserve_liquidity_supply: k[2],
+                deposit_collateral_ata: k[3],
+                lending_market: k[4],
+                lending_market_authority: k[5],
+                pyth_oracle: k[6],
+                switchboard_oracle: k[7],
+            },
+        ),
+        (
+            3,
+            encode_fields(&k[..6], &[]),
+            StrategyType::MangoSupply {
+                group: k[0],
+                account: k[1],
+                bank: k[2],
+                vault: k[3],
+                pyth_oracle: k[4],
+                switchboard_oracle: k[5],
+            },
+        ),
+        (
+            4,
+            encode_fields(&k[..7], &[3, 1]),
+            StrategyType::DriftSupply {
+                state: k[0],
+                signer: k[1],
+                spot_market: k[2],
+                spot_market_vault: k[3],
+                perp_market: k[4],
+                spot_pyth_oracle: k[5],
+                perp_pyth_oracle: k[6],
+                sub_account_id: 3,
+                market_index: 1,
+            },
+        ),
+        (
+            5,
+            encode_fields(&k[..3], &[1]),
+            StrategyType::DriftInsuranceFund {
+                state: k[0],
+                spot_market: k[1],
+                spot_market_vault: k[2],
+                market_index: 1,
+            },
+        ),
+    ];
+
+    for (variant, fields, expected) in cases {
+        let data = encode_strategy("usdc strategy", 7, variant, &fields);
+        let strategy = load(&data).unwrap();
+
+        assert_eq!("usdc strategy", strategy.metadata.name);
+        assert_eq!(7, strategy.metadata.strategy_id);
+        assert_eq!(USDC_MINT, strategy.metadata.asset_mint);
+        assert_eq!(CRT_VAULT, strategy.metadata.vault);
+        assert_eq!(expected, strategy.strategy_type);
+
+        // every truncation errors instead of panicking
+        for len in 0..data.len() {
+            assert!(load(&data[..len]).is_err());
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_load_strategy_account_errors() {
+    // the program has no strategy type past drift insurance fund
+    let data = encode_strategy("bad", 0, 6, &encode_fields(&keys(4), &[5]));
+    let err = load(&data).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::UnknownStrategyType),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+
+    let mut data = encode_strategy("marginfi", 0, 0, &encode_fields(&keys(6), &[]));
+    data[0] ^= 0xff;
+    let err = load(&data).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::InvalidAccountDiscriminator),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+
+    // a garbage name length is rejected before reading past the account
+    let mut data = encode_strategy("marginfi", 0, 0, &encode_fields(&keys(6), &[]));
+    data[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+    let err = load(&data).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CarrotAmmError>(),
+        Some(CarrotAmmError::AccountDataTooShort { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_load_strategy_account_owner() {
+    let address = Pubkey::new_unique();
+    let mut account = strategy_account(encode_strategy(
+        "marginfi",
+        0,
+        0,
+        &encode_fields(&keys(6), &[]),
+    ));
+    Strategy::load(&address, &account).unwrap();
+
+    // same bytes owned by another program are rejected
+    account.owner = Pubkey::new_unique();
+    let err = Strategy::load(&address, &account).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::InvalidAccountOwner {
+            account: address,
+            owner: account.owner,
+        }),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
@@ -0,0 +1,68 @@
+use amm::constants::{CRT_MINT, USDC_MINT};
+use amm::errors::CarrotAmmError;
+use amm::CarrotAmm;
+use jupiter_amm_interface::{Amm, QuoteParams, SwapMode};
+use solana_sdk::pubkey::Pubkey;
+
+mod utils;
+use utils::*;
+
+fn quote_err(
+    carrot_amm: &CarrotAmm,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+) -> CarrotAmmError {
+    let err = carrot_amm
+        .quote(&QuoteParams {
+            input_mint,
+            output_mint,
+            amount,
+            swap_mode: SwapMode::ExactIn,
+        })
+        .unwrap_err();
+    *err.downcast_ref::<CarrotAmmError>().unwrap()
+}
+
+#[tokio::test]
+async fn test_negative_price() {
+    let carrot_amm = with_usdc_price(init_amm(), -100_000_000, -8);
+
+    assert_eq!(
+        CarrotAmmError::NegativePrice,
+        quote_err(&carrot_amm, USDC_MINT, CRT_MINT, 1_000_000)
+    );
+    assert_eq!(
+        CarrotAmmError::NegativePrice,
+        quote_err(&carrot_amm, CRT_MINT, USDC_MINT, 1_000_000)
+    );
+}
+
+#[tokio::test]
+async fn test_zero_price() {
+    let carrot_amm = with_usdc_price(init_amm(), 0, -8);
+
+    // converting usd back to tokens divides by the price
+    assert_eq!(
+        CarrotAmmError::DivisionByZero,
+        quote_err(&carrot_amm, CRT_MINT, USDC_MINT, 1_000_000)
+    );
+}
+
+#[tokio::test]
+async fn test_overflow_is_not_capped() {
+    let carrot_amm = with_usdc_price(init_amm(), i64::MAX, -1);
+
+    // the usd value of the deposit no longer fits in a u128
+    assert_eq!(
+        CarrotAmmError::MathOverflow,
+        quote_err(&carrot_amm, USDC_MINT, CRT_MINT, u64::MAX)
+    );
+
+    // a deposit worth more shares than fit in a u64 errors instead of quoting u64::MAX
+    let carrot_amm = with_usdc_price(init_amm(), 1_000_000_000_000, -8);
+    assert_eq!(
+        CarrotAmmError::MathOverflow,
+        quote_err(&carrot_amm, USDC_MINT, CRT_MINT, u64::MAX)
+    );
+}
@@ -0,0 +1,121 @@
+use amm::constants::{CRT_VAULT, USDC_FEED_ID, USDC_MINT, USDC_ORACLE, USDT_FEED_ID, USDT_ORACLE};
+use amm::errors::CarrotAmmError;
+use amm::state::{PriceUpdateV2, Vault, VerificationLevel};
+use amm::CarrotAmm;
+use jupiter_amm_interface::{AccountMap, Amm};
+
+mod utils;
+use utils::*;
+
+// offset of the verification level inside the price update account
+const VERIFICATION_LEVEL_OFFSET: usize = 8 + 32;
+
+fn new_amm(account_map: &AccountMap) -> CarrotAmm {
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX)
+}
+
+// re-encodes a fully verified price update as partially verified with num_signatures
+fn as_partially_verified(full_data: &[u8], num_signatures: u8) -> Vec<u8> {
+    let mut data = full_data[..VERIFICATION_LEVEL_OFFSET].to_vec();
+    data.extend_from_slice(&[0x00, num_signatures]);
+    data.extend_from_slice(&full_data[VERIFICATION_LEVEL_OFFSET + 1..full_data.len() - 1]);
+    data
+}
+
+#[tokio::test]
+async fn test_oracle_feed_id_mismatch() {
+    let mut account_map = load_account_map_from_file();
+
+    // point the usdc oracle account at the usdt feed
+    let usdt_oracle = account_map.get(&USDT_ORACLE).unwrap().clone();
+    account_map.insert(USDC_ORACLE, usdt_oracle);
+
+    let mut carrot_amm = new_amm(&account_map);
+    let err = carrot_amm.update(&account_map).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::OracleFeedIdMismatch),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+
+    // the usdt feed is accepted once it is the expected feed for usdc
+    carrot_amm.oracle_feed_ids.insert(USDC_MINT, USDT_FEED_ID);
+    carrot_amm.update(&account_map).unwrap();
+}
+
+#[tokio::test]
+async fn test_oracle_feed_id_pinned_on_first_update() {
+    let mut account_map = load_account_map_from_file();
+
+    // an asset without an expected feed id takes the feed of its oracle
+    let mut carrot_amm = new_amm(&account_map);
+    carrot_amm.oracle_feed_ids.remove(&USDC_MINT);
+    carrot_amm.update(&account_map).unwrap();
+    assert_eq!(
+        Some(&USDC_FEED_ID),
+        carrot_amm.oracle_feed_ids.get(&USDC_MINT)
+    );
+
+    // and rejects any other feed from then on
+    let usdt_oracle = account_map.get(&USDT_ORACLE).unwrap().clone();
+    account_map.insert(USDC_ORACLE, usdt_oracle);
+    let err = carrot_amm.update(&account_map).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::OracleFeedIdMismatch),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
+
+#[tokio::test]
+async fn test_oracle_verification_level() {
+    let mut account_map = load_account_map_from_file();
+
+    let full_data = account_map.get(&USDC_ORACLE).unwrap().data.clone();
+    let partial_data = as_partially_verified(&full_data, 3);
+    account_map.get_mut(&USDC_ORACLE).unwrap().data = partial_data.clone();
+
+    // the re-encoded account decodes to the same price
+    let full = PriceUpdateV2::load(&full_data).unwrap();
+    let partial = PriceUpdateV2::load(&partial_data).unwrap();
+    assert_eq!(VerificationLevel::Full, full.verification_level);
+    assert_eq!(
+        VerificationLevel::Partial { num_signatures: 3 },
+        partial.verification_level
+    );
+    assert_eq!(full.price_message.feed_id, partial.price_message.feed_id);
+    assert_eq!(full.price_message.price, partial.price_message.price);
+    assert_eq!(full.posted_slot, partial.posted_slot);
+
+    // full verification is required by default
+    let mut carrot_amm = new_amm(&account_map);
+    let err = carrot_amm.update(&account_map).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::OracleVerificationLevelTooLow),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+
+    // partial updates are rejected below the signature threshold
+    carrot_amm.oracle_min_verification_level = VerificationLevel::Partial { num_signatures: 5 };
+    let err = carrot_amm.update(&account_map).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::OracleVerificationLevelTooLow),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+
+    // and accepted at or above it
+    carrot_amm.oracle_min_verification_level = VerificationLevel::Partial { num_signatures: 3 };
+    carrot_amm.update(&account_map).unwrap();
+}
+
+#[tokio::test]
+async fn test_verification_level_gte() {
+    let full = VerificationLevel::Full;
+    let partial = |num_signatures| VerificationLevel::Partial { num_signatures };
+
+    assert!(full.gte(full));
+    assert!(full.gte(partial(13)));
+    assert!(!partial(13).gte(full));
+    assert!(partial(5).gte(partial(5)));
+    assert!(partial(6).gte(partial(5)));
+    assert!(!partial(4).gte(partial(5)));
+}
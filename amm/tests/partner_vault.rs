@@ -0,0 +1,182 @@
+use amm::constants::{
+    CRT_MINT, CRT_VAULT, PYUSD_MINT, USDC_MINT, USDC_ORACLE, USDC_VAULT_ATA, USDT_MINT,
+};
+use amm::errors::CarrotAmmError;
+use amm::{
+    state::{Asset, Vault},
+    CarrotAmm,
+};
+use jupiter_amm_interface::{AccountMap, Amm, QuoteParams, SwapMode};
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
+
+mod utils;
+use utils::*;
+
+// offset of the shares mint inside the vault account, after discriminator and authority
+const VAULT_SHARES_OFFSET: usize = 8 + 32;
+
+// offset of the assets inside the vault account, after the fixed fields and the vec length
+const VAULT_ASSETS_OFFSET: usize = 8 + 32 + 32 + 30 + 1 + 2 + 2 + 4;
+
+// offsets of the mint and oracle inside an asset
+const ASSET_MINT_OFFSET: usize = 2;
+const ASSET_ORACLE_OFFSET: usize = 2 + 32 + 1 + 32;
+
+// offset of the feed id inside a fully verified price update account
+const FEED_ID_OFFSET: usize = 8 + 32 + 1;
+
+// moves the fixture vault and its shares mint to new addresses
+fn load_partner_account_map(vault: Pubkey, shares_mint: Pubkey) -> AccountMap {
+    let mut account_map = load_account_map_from_file();
+
+    let mut vault_account = account_map.remove(&CRT_VAULT).unwrap();
+    vault_account.data[VAULT_SHARES_OFFSET..VAULT_SHARES_OFFSET + 32]
+        .copy_from_slice(shares_mint.as_ref());
+    account_map.insert(vault, vault_account);
+
+    let shares_account = account_map.remove(&CRT_MINT).unwrap();
+    account_map.insert(shares_mint, shares_account);
+
+    account_map
+}
+
+// replaces the usdc asset of the vault with a mint and oracle unknown to the crate
+fn with_new_asset(
+    mut account_map: AccountMap,
+    vault: Pubkey,
+    mint: Pubkey,
+    oracle: Pubkey,
+) -> AccountMap {
+    let vault_data = &mut account_map.get_mut(&vault).unwrap().data;
+    let index = Vault::load(vault_data)
+        .unwrap()
+        .assets
+        .iter()
+        .position(|asset| asset.mint.eq(&USDC_MINT))
+        .unwrap();
+    let asset_offset = VAULT_ASSETS_OFFSET + index * Asset::SPACE;
+    vault_data[asset_offset + ASSET_MINT_OFFSET..asset_offset + ASSET_MINT_OFFSET + 32]
+        .copy_from_slice(mint.as_ref());
+    vault_data[asset_offset + ASSET_ORACLE_OFFSET..asset_offset + ASSET_ORACLE_OFFSET + 32]
+        .copy_from_slice(oracle.as_ref());
+
+    // the vault ata holds the new mint
+    account_map.get_mut(&USDC_VAULT_ATA).unwrap().data[..32].copy_from_slice(mint.as_ref());
+
+    let mint_account = account_map.remove(&USDC_MINT).unwrap();
+    account_map.insert(mint, mint_account);
+
+    let mut oracle_account = account_map.remove(&USDC_ORACLE).unwrap();
+    oracle_account.data[FEED_ID_OFFSET..FEED_ID_OFFSET + 32].fill(7);
+    account_map.insert(oracle, oracle_account);
+
+    account_map
+}
+
+#[tokio::test]
+async fn test_partner_vault() {
+    let vault = Pubkey::new_unique();
+    let shares_mint = Pubkey::new_unique();
+    let account_map = load_partner_account_map(vault, shares_mint);
+
+    let vault_state = Vault::load(&account_map.get(&vault).unwrap().data).unwrap();
+    let mut carrot_amm = CarrotAmm::new(vault, vault_state, u64::MAX);
+    carrot_amm.update(&account_map).unwrap();
+
+    assert_eq!(vault, carrot_amm.key());
+    assert_eq!(
+        vec![shares_mint, USDC_MINT, USDT_MINT, PYUSD_MINT],
+        carrot_amm.get_reserve_mints()
+    );
+
+    // same vault data priced through the new shares mint
+    let issue_quote = carrot_amm
+        .quote(&QuoteParams {
+            input_mint: USDC_MINT,
+            output_mint: shares_mint,
+            amount: 1_000_000_000,
+            swap_mode: SwapMode::ExactIn,
+        })
+        .unwrap();
+    assert_eq!(9684056623, issue_quote.out_amount);
+
+    let redeem_quote = carrot_amm
+        .quote(&QuoteParams {
+            input_mint: shares_mint,
+            output_mint: USDC_MINT,
+            amount: 1_000,
+            swap_mode: SwapMode::ExactIn,
+        })
+        .unwrap();
+    assert_eq!(103, redeem_quote.out_amount);
+    assert_eq!(shares_mint, redeem_quote.fee_mint);
+
+    // the old CRT mint is not part of this vault
+    let crt_quote = carrot_amm.quote(&QuoteParams {
+        input_mint: CRT_MINT,
+        output_mint: USDC_MINT,
+        amount: 1_000,
+        swap_mode: SwapMode::ExactIn,
+    });
+    assert!(crt_quote.is_err());
+
+    // swap accounts must reference the partner vault and shares mint
+    let account_metas: Vec<AccountMeta> = carrot_amm
+        .carrot_swap(
+            shares_mint,
+            USDC_MINT,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        )
+        .try_into()
+        .unwrap();
+    assert_eq!(AccountMeta::new(vault, false), account_metas[0]);
+    assert_eq!(AccountMeta::new(shares_mint, false), account_metas[1]);
+}
+
+#[tokio::test]
+async fn test_partner_vault_new_asset() {
+    let vault = Pubkey::new_unique();
+    let shares_mint = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let oracle = Pubkey::new_unique();
+    let crt_asset_map = load_partner_account_map(vault, shares_mint);
+    let mut account_map = with_new_asset(crt_asset_map.clone(), vault, mint, oracle);
+
+    // the amm is built the way the router builds it, with no feed ids passed in
+    let vault_state = Vault::load(&account_map.get(&vault).unwrap().data).unwrap();
+    let mut carrot_amm = CarrotAmm::new(vault, vault_state, u64::MAX);
+    carrot_amm.update(&account_map).unwrap();
+    assert_eq!(
+        vec![shares_mint, mint, USDT_MINT, PYUSD_MINT],
+        carrot_amm.get_reserve_mints()
+    );
+    assert_eq!(Some(&[7; 32]), carrot_amm.oracle_feed_ids.get(&mint));
+
+    // priced like the usdc asset it replaced
+    let params = |input_mint| QuoteParams {
+        input_mint,
+        output_mint: shares_mint,
+        amount: 1_000_000_000,
+        swap_mode: SwapMode::ExactIn,
+    };
+    let mut usdc_amm = CarrotAmm::new(
+        vault,
+        Vault::load(&crt_asset_map.get(&vault).unwrap().data).unwrap(),
+        u64::MAX,
+    );
+    usdc_amm.update(&crt_asset_map).unwrap();
+    assert_eq!(
+        usdc_amm.quote(&params(USDC_MINT)).unwrap().out_amount,
+        carrot_amm.quote(&params(mint)).unwrap().out_amount
+    );
+
+    // the pinned feed is enforced on later updates
+    account_map.get_mut(&oracle).unwrap().data[FEED_ID_OFFSET..FEED_ID_OFFSET + 32].fill(8);
+    let err = carrot_amm.update(&account_map).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::OracleFeedIdMismatch),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
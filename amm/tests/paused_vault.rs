@@ -0,0 +1,75 @@
+use amm::constants::{CRT_MINT, CRT_VAULT, USDC_MINT};
+use jupiter_amm_interface::{Amm, QuoteParams, SwapMode, SwapParams};
+use solana_sdk::pubkey::Pubkey;
+
+mod utils;
+use utils::*;
+
+// offset of the paused flag inside the vault account
+// discriminator, authority, shares and fee come before it
+const VAULT_PAUSED_OFFSET: usize = 8 + 32 + 32 + 30;
+
+#[tokio::test]
+async fn test_paused_vault() {
+    // init account map from filesystem and pause the vault
+    let mut account_map = load_account_map_from_file();
+    account_map.get_mut(&CRT_VAULT).unwrap().data[VAULT_PAUSED_OFFSET] = 1;
+
+    // init amm
+    let mut carrot_amm = init_amm_with_accounts(&account_map);
+    assert!(carrot_amm.vault_state.paused);
+    assert!(!carrot_amm.is_active());
+
+    for (input_mint, output_mint) in [(USDC_MINT, CRT_MINT), (CRT_MINT, USDC_MINT)] {
+        for swap_mode in [SwapMode::ExactIn, SwapMode::ExactOut] {
+            let quote_result = carrot_amm.quote(&QuoteParams {
+                input_mint,
+                output_mint,
+                amount: 1_000,
+                swap_mode,
+            });
+            assert_eq!(
+                "Vault is Paused",
+                quote_result.unwrap_err().to_string(),
+                "{} -> {}",
+                input_mint,
+                output_mint
+            );
+        }
+
+        // swap instructions are rejected too
+        let swap_result = carrot_amm.get_swap_and_account_metas(&SwapParams {
+            in_amount: 1_000,
+            out_amount: 1_000,
+            source_mint: input_mint,
+            destination_mint: output_mint,
+            source_token_account: Pubkey::new_unique(),
+            destination_token_account: Pubkey::new_unique(),
+            token_transfer_authority: Pubkey::new_unique(),
+            open_order_address: None,
+            quote_mint_to_referrer: None,
+            jupiter_program_id: &Pubkey::new_unique(),
+            missing_dynamic_accounts_as_default: false,
+        });
+        assert_eq!(
+            "Vault is Paused",
+            swap_result.err().unwrap().to_string(),
+            "{} -> {}",
+            input_mint,
+            output_mint
+        );
+    }
+
+    // unpause and make sure the market is usable again
+    account_map.get_mut(&CRT_VAULT).unwrap().data[VAULT_PAUSED_OFFSET] = 0;
+    carrot_amm.update(&account_map).unwrap();
+    assert!(carrot_amm.is_active());
+
+    let quote_result = carrot_amm.quote(&QuoteParams {
+        input_mint: USDC_MINT,
+        output_mint: CRT_MINT,
+        amount: 1_000,
+        swap_mode: SwapMode::ExactIn,
+    });
+    assert!(quote_result.is_ok());
+}
@@ -0,0 +1,139 @@
+use amm::constants::{CRT_MINT, USDC_MINT};
+use amm::errors::CarrotAmmError;
+use amm::CarrotAmm;
+use jupiter_amm_interface::{Amm, QuoteParams, SwapMode};
+use solana_sdk::pubkey::Pubkey;
+
+mod utils;
+use utils::*;
+
+fn quote(
+    carrot_amm: &CarrotAmm,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+    swap_mode: SwapMode,
+) -> anyhow::Result<u64> {
+    let quote = carrot_amm.quote(&QuoteParams {
+        input_mint,
+        output_mint,
+        amount,
+        swap_mode,
+    })?;
+    Ok(match swap_mode {
+        SwapMode::ExactIn => quote.out_amount,
+        SwapMode::ExactOut => quote.in_amount,
+    })
+}
+
+// every quote direction the usdc price feeds into
+fn quotes(carrot_amm: &CarrotAmm) -> Vec<u64> {
+    [
+        (USDC_MINT, CRT_MINT, 1_000_000_000, SwapMode::ExactIn),
+        (CRT_MINT, USDC_MINT, 100_000_000, SwapMode::ExactIn),
+        (USDC_MINT, CRT_MINT, 1_000_000_000, SwapMode::ExactOut),
+        (CRT_MINT, USDC_MINT, 1_000_000, SwapMode::ExactOut),
+    ]
+    .into_iter()
+    .map(|(input_mint, output_mint, amount, swap_mode)| {
+        quote(carrot_amm, input_mint, output_mint, amount, swap_mode).unwrap()
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn test_equivalent_exponents_quote_the_same() {
+    // $1.0001 published with every exponent from -18 to -4
+    let expected = quotes(&with_usdc_price(init_amm(), 100_010_000, -8));
+    for k in -10_i32..=4 {
+        let (price, expo) = if k >= 0 {
+            (100_010_000 / 10_i64.pow(k as u32), -8 + k)
+        } else {
+            (100_010_000 * 10_i64.pow(k.unsigned_abs()), -8 + k)
+        };
+        let carrot_amm = with_usdc_price(init_amm(), price, expo);
+        assert_eq!(expected, quotes(&carrot_amm), "expo: {}", expo);
+    }
+}
+
+#[tokio::test]
+async fn test_zero_and_positive_exponents() {
+    // $1 and $10 published without decimals
+    for (price, expo, equivalent_price) in [
+        (1, 0, 100_000_000),
+        (10, 0, 1_000_000_000),
+        (1, 1, 1_000_000_000),
+    ] {
+        let expected = quotes(&with_usdc_price(init_amm(), equivalent_price, -8));
+        let carrot_amm = with_usdc_price(init_amm(), price, expo);
+        assert_eq!(
+            expected,
+            quotes(&carrot_amm),
+            "price: {} expo: {}",
+            price,
+            expo
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_extreme_exponents() {
+    // a price far below a unit is worth nothing at 9 decimals of usd precision
+    let carrot_amm = with_usdc_price(init_amm(), i64::MAX, -60);
+    assert_eq!(
+        0,
+        quote(
+            &carrot_amm,
+            USDC_MINT,
+            CRT_MINT,
+            1_000_000_000,
+            SwapMode::ExactIn
+        )
+        .unwrap()
+    );
+
+    // and the tokens owed for a redemption no longer fit in a u64
+    let err = quote(
+        &carrot_amm,
+        CRT_MINT,
+        USDC_MINT,
+        100_000_000,
+        SwapMode::ExactIn,
+    )
+    .unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::MathOverflow),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+
+    // very large prices overflow the tvl instead of panicking
+    for expo in [30, i32::MAX] {
+        let carrot_amm = with_usdc_price(init_amm(), 1, expo);
+        let err = quote(
+            &carrot_amm,
+            USDC_MINT,
+            CRT_MINT,
+            1_000_000,
+            SwapMode::ExactIn,
+        )
+        .unwrap_err();
+        assert_eq!(
+            Some(&CarrotAmmError::MathOverflow),
+            err.downcast_ref::<CarrotAmmError>()
+        );
+    }
+
+    // very small prices never panic either
+    let carrot_amm = with_usdc_price(init_amm(), 1, i32::MIN);
+    assert_eq!(
+        0,
+        quote(
+            &carrot_amm,
+            USDC_MINT,
+            CRT_MINT,
+            u64::MAX,
+            SwapMode::ExactIn
+        )
+        .unwrap()
+    );
+}
@@ -0,0 +1,364 @@
+use amm::constants::{CRT_MINT, CRT_VAULT, PYUSD_ORACLE, USDC_MINT, USDC_ORACLE, USDT_ORACLE};
+use amm::state::{PriceSource, PriceUpdateV2, RoundingMode, Vault};
+use amm::{instructions, CarrotAmm};
+use jupiter_amm_interface::{AccountMap, Amm, ClockRef, QuoteParams, SwapMode};
+use solana_program_test::BanksClient;
+use solana_sdk::{
+    clock::Clock,
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+mod utils;
+use utils::*;
+
+// offsets of the price fields inside a fully verified price update account
+const PRICE_OFFSET: usize = 8 + 32 + 1 + 32;
+const EMA_PRICE_OFFSET: usize = PRICE_OFFSET + 8 + 8 + 4 + 8 + 8;
+const EMA_CONF_OFFSET: usize = EMA_PRICE_OFFSET + 8;
+
+const ORACLES: [solana_sdk::pubkey::Pubkey; 3] = [USDC_ORACLE, USDT_ORACLE, PYUSD_ORACLE];
+
+fn quote(account_map: &AccountMap, price_source: PriceSource, params: &QuoteParams) -> u64 {
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+    carrot_amm.price_source = price_source;
+    carrot_amm.update(account_map).unwrap();
+    carrot_amm.quote(params).unwrap().out_amount
+}
+
+// amm loaded from the bank accounts and priced at the bank clock
+async fn load_amm_from_bank(banks_client: &mut BanksClient, account_map: &AccountMap) -> CarrotAmm {
+    let account_map = refresh_account_map(banks_client, account_map).await;
+    let clock: Clock = banks_client.get_sysvar().await.unwrap();
+
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+    carrot_amm.clock_ref = Some(ClockRef::from(clock));
+    carrot_amm.update(&account_map).unwrap();
+    carrot_amm
+}
+
+async fn process(banks_client: &mut BanksClient, payer: &Keypair, ix: Instruction) {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash,
+    );
+
+    banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap()
+        .result
+        .unwrap();
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+// rewrites the ema price of every oracle with f(data)
+fn with_ema_price(account_map: &AccountMap, f: impl Fn(&[u8]) -> i64) -> AccountMap {
+    let mut account_map = account_map.clone();
+    for oracle in ORACLES {
+        let data = &mut account_map.get_mut(&oracle).unwrap().data;
+        let ema_price = f(data);
+        data[EMA_PRICE_OFFSET..EMA_PRICE_OFFSET + 8].copy_from_slice(&ema_price.to_le_bytes());
+    }
+    account_map
+}
+
+fn issue_params() -> QuoteParams {
+    QuoteParams {
+        input_mint: USDC_MINT,
+        output_mint: CRT_MINT,
+        amount: 1_000_000_000,
+        swap_mode: SwapMode::ExactIn,
+    }
+}
+
+fn redeem_params() -> QuoteParams {
+    QuoteParams {
+        input_mint: CRT_MINT,
+        output_mint: USDC_MINT,
+        amount: 100_000_000,
+        swap_mode: SwapMode::ExactIn,
+    }
+}
+
+#[tokio::test]
+async fn test_price_feed_message_get_price() {
+    let account_map = load_account_map_from_file();
+
+    for oracle in ORACLES {
+        let data = &account_map.get(&oracle).unwrap().data;
+        let feed = PriceUpdateV2::load(data).unwrap().price_message;
+
+        for rounding_mode in [
+            RoundingMode::RoundUp,
+            RoundingMode::RoundDown,
+            RoundingMode::Avg,
+        ] {
+            assert_eq!(feed.price, feed.get_price(PriceSource::Spot, rounding_mode));
+            assert_eq!(
+                feed.ema_price,
+                feed.get_price(PriceSource::Ema, rounding_mode)
+            );
+        }
+
+        let conf = feed.ema_conf as i64;
+        assert_eq!(
+            feed.price.max(feed.ema_price),
+            feed.get_price(PriceSource::Conservative, RoundingMode::RoundUp)
+        );
+        assert_eq!(
+            feed.price.min(feed.ema_price),
+            feed.get_price(PriceSource::Conservative, RoundingMode::RoundDown)
+        );
+        assert_eq!(
+            feed.ema_price + conf,
+            feed.get_price(PriceSource::ConfidenceAdjusted, RoundingMode::RoundUp)
+        );
+        assert_eq!(
+            feed.ema_price - conf,
+            feed.get_price(PriceSource::ConfidenceAdjusted, RoundingMode::RoundDown)
+        );
+        assert_eq!(
+            feed.ema_price,
+            feed.get_price(PriceSource::ConfidenceAdjusted, RoundingMode::Avg)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_default_price_source_is_ema() {
+    let account_map = load_account_map_from_file();
+
+    assert_eq!(PriceSource::Ema, PriceSource::default());
+    assert_eq!(
+        9684056623,
+        quote(&account_map, PriceSource::default(), &issue_params())
+    );
+}
+
+#[tokio::test]
+async fn test_spot_price_source() {
+    let account_map = load_account_map_from_file();
+
+    // spot quotes match ema quotes on oracles whose ema price equals the spot price
+    let spot_as_ema = with_ema_price(&account_map, |data| read_i64(data, PRICE_OFFSET));
+    for params in [issue_params(), redeem_params()] {
+        assert_eq!(
+            quote(&spot_as_ema, PriceSource::Ema, &params),
+            quote(&account_map, PriceSource::Spot, &params)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_confidence_adjusted_price_source() {
+    let account_map = load_account_map_from_file();
+
+    // issue prices every asset at ema + conf
+    let ema_up = with_ema_price(&account_map, |data| {
+        read_i64(data, EMA_PRICE_OFFSET) + read_i64(data, EMA_CONF_OFFSET)
+    });
+    assert_eq!(
+        quote(&ema_up, PriceSource::Ema, &issue_params()),
+        quote(
+            &account_map,
+            PriceSource::ConfidenceAdjusted,
+            &issue_params()
+        )
+    );
+
+    // redeem prices every asset at ema - conf
+    let ema_down = with_ema_price(&account_map, |data| {
+        read_i64(data, EMA_PRICE_OFFSET) - read_i64(data, EMA_CONF_OFFSET)
+    });
+    assert_eq!(
+        quote(&ema_down, PriceSource::Ema, &redeem_params()),
+        quote(
+            &account_map,
+            PriceSource::ConfidenceAdjusted,
+            &redeem_params()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_conservative_price_source() {
+    let account_map = load_account_map_from_file();
+
+    let max = with_ema_price(&account_map, |data| {
+        read_i64(data, EMA_PRICE_OFFSET).max(read_i64(data, PRICE_OFFSET))
+    });
+    assert_eq!(
+        quote(&max, PriceSource::Ema, &issue_params()),
+        quote(&account_map, PriceSource::Conservative, &issue_params())
+    );
+
+    let min = with_ema_price(&account_map, |data| {
+        read_i64(data, EMA_PRICE_OFFSET).min(read_i64(data, PRICE_OFFSET))
+    });
+    assert_eq!(
+        quote(&min, PriceSource::Ema, &redeem_params()),
+        quote(&account_map, PriceSource::Conservative, &redeem_params())
+    );
+}
+
+#[tokio::test]
+async fn test_update_caches_directional_prices() {
+    let account_map = load_account_map_from_file();
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+    carrot_amm.price_source = PriceSource::ConfidenceAdjusted;
+    carrot_amm.update(&account_map).unwrap();
+
+    for asset in carrot_amm.asset_state.iter() {
+        let feed = asset.oracle_price_feed;
+        let conf = feed.ema_conf as i64;
+        assert_eq!(feed.ema_price + conf, asset.oracle_price_up);
+        assert_eq!(feed.ema_price - conf, asset.oracle_price_down);
+        assert_eq!(asset.oracle_price_up, asset.get_price(true));
+        assert_eq!(asset.oracle_price_down, asset.get_price(false));
+    }
+
+    // changing the price source only takes effect on the next update
+    let issue_out = carrot_amm.quote(&issue_params()).unwrap().out_amount;
+    carrot_amm.price_source = PriceSource::Ema;
+    assert_eq!(
+        issue_out,
+        carrot_amm.quote(&issue_params()).unwrap().out_amount
+    );
+    carrot_amm.update(&account_map).unwrap();
+    assert_eq!(
+        9684056623,
+        carrot_amm.quote(&issue_params()).unwrap().out_amount
+    );
+}
+
+#[tokio::test]
+async fn test_default_prices_match_program() {
+    // move the spot price and ema confidence of each oracle away from its ema by a different
+    // amount, only the ema reproduces the program
+    let mut account_map = load_account_map_from_file();
+    for (oracle, (price_bps, conf_bps)) in
+        ORACLES
+            .into_iter()
+            .zip([(10_500, 100), (9_700, 300), (10_200, 50)])
+    {
+        let data = &mut account_map.get_mut(&oracle).unwrap().data;
+        let ema_price = read_i64(data, EMA_PRICE_OFFSET);
+        let price = ema_price * price_bps / 10_000;
+        let ema_conf = (ema_price * conf_bps / 10_000) as u64;
+        data[PRICE_OFFSET..PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+        data[EMA_CONF_OFFSET..EMA_CONF_OFFSET + 8].copy_from_slice(&ema_conf.to_le_bytes());
+    }
+
+    for (input_mint, token_program) in fixture_input_mints() {
+        let TestEnv {
+            mut banks_client,
+            payer,
+            account_map,
+            payer_shares_ata,
+            payer_input_mint_ata,
+            ..
+        } = setup_test_env_with_accounts(
+            account_map.clone(),
+            input_mint,
+            token_program,
+            1_000_000_000,
+        )
+        .await;
+
+        // issue priced at the cached round up price
+        let carrot_amm = load_amm_from_bank(&mut banks_client, &account_map).await;
+        let asset = carrot_amm.get_asset_by_mint(&input_mint).unwrap();
+        let ema_price = asset.oracle_price_feed.ema_price;
+        assert_eq!(ema_price, asset.oracle_price_up);
+        assert_eq!(ema_price, asset.oracle_price_down);
+
+        let issue_params = QuoteParams {
+            input_mint,
+            output_mint: CRT_MINT,
+            amount: 1_000_000_000,
+            swap_mode: SwapMode::ExactIn,
+        };
+        let issue_quote = carrot_amm.quote(&issue_params).unwrap();
+
+        // the other sources price away from the ema and would miss what the program mints
+        let bank_account_map = refresh_account_map(&mut banks_client, &account_map).await;
+        for price_source in [
+            PriceSource::Spot,
+            PriceSource::Conservative,
+            PriceSource::ConfidenceAdjusted,
+        ] {
+            assert_ne!(
+                issue_quote.out_amount,
+                quote(&bank_account_map, price_source, &issue_params)
+            );
+        }
+
+        let swap = carrot_amm.carrot_swap(
+            input_mint,
+            CRT_MINT,
+            payer_input_mint_ata,
+            payer_shares_ata,
+            payer.pubkey(),
+        );
+        process(
+            &mut banks_client,
+            &payer,
+            instructions::issue(swap, issue_quote.in_amount).unwrap(),
+        )
+        .await;
+        let shares = get_token_balance(&mut banks_client, payer_shares_ata).await;
+        assert_eq!(issue_quote.out_amount, shares, "input_mint: {input_mint}");
+
+        // redeem priced at the cached round down price
+        let carrot_amm = load_amm_from_bank(&mut banks_client, &account_map).await;
+        let redeem_params = QuoteParams {
+            input_mint: CRT_MINT,
+            output_mint: input_mint,
+            amount: shares / 2,
+            swap_mode: SwapMode::ExactIn,
+        };
+        let redeem_quote = carrot_amm.quote(&redeem_params).unwrap();
+        let bank_account_map = refresh_account_map(&mut banks_client, &account_map).await;
+        for price_source in [
+            PriceSource::Spot,
+            PriceSource::Conservative,
+            PriceSource::ConfidenceAdjusted,
+        ] {
+            assert_ne!(
+                redeem_quote.out_amount,
+                quote(&bank_account_map, price_source, &redeem_params)
+            );
+        }
+
+        let balance = get_token_balance(&mut banks_client, payer_input_mint_ata).await;
+        let swap = carrot_amm.carrot_swap(
+            CRT_MINT,
+            input_mint,
+            payer_shares_ata,
+            payer_input_mint_ata,
+            payer.pubkey(),
+        );
+        process(
+            &mut banks_client,
+            &payer,
+            instructions::redeem(swap, redeem_quote.in_amount).unwrap(),
+        )
+        .await;
+        let redeemed = get_token_balance(&mut banks_client, payer_input_mint_ata).await - balance;
+        assert_eq!(
+            redeem_quote.out_amount, redeemed,
+            "input_mint: {input_mint}"
+        );
+    }
+}
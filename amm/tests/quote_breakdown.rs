@@ -0,0 +1,125 @@
+use amm::constants::{CRT_MINT, USDC_MINT, USDT_MINT};
+use amm::errors::CarrotAmmError;
+use jupiter_amm_interface::{Amm, QuoteParams, SwapMode};
+use rust_decimal::Decimal;
+
+mod utils;
+use utils::*;
+
+#[tokio::test]
+async fn test_quote_breakdown_issue() {
+    let carrot_amm = init_amm();
+    let quote_params = QuoteParams {
+        input_mint: USDC_MINT,
+        output_mint: CRT_MINT,
+        amount: 1_000_000_000,
+        swap_mode: SwapMode::ExactIn,
+    };
+
+    let breakdown = carrot_amm.quote_detailed(&quote_params).unwrap();
+    let quote = carrot_amm.quote(&quote_params).unwrap();
+    assert_eq!(quote.in_amount, breakdown.quote().in_amount);
+    assert_eq!(quote.out_amount, breakdown.quote().out_amount);
+    assert_eq!(9684056623, breakdown.out_amount);
+
+    assert!(!breakdown.is_redeem);
+    assert_eq!(USDC_MINT, breakdown.asset_mint);
+    let usdc = carrot_amm.get_asset_by_mint(&USDC_MINT).unwrap();
+    assert_eq!(usdc.oracle_price_up, breakdown.asset_price);
+    assert_eq!(usdc.oracle_price_expo, breakdown.asset_price_expo);
+
+    // nav is priced with the issue rounding
+    let nav = breakdown.nav;
+    let shares_state = carrot_amm.shares_state.unwrap();
+    assert_eq!(shares_state.supply, nav.shares_supply);
+    assert_eq!(
+        carrot_amm
+            .vault_state
+            .get_tvl(&carrot_amm.asset_state, true)
+            .unwrap(),
+        nav.vault_tvl
+    );
+    assert_eq!(
+        nav.shares_supply
+            + nav.performance_fee_shares
+            + nav.management_fee_shares
+            + nav.accumulated_fee_shares,
+        nav.adjusted_shares_supply
+    );
+
+    // shares out are the deposit's share of the vault
+    assert_eq!(
+        breakdown.usd_value * nav.adjusted_shares_supply as u128 / nav.vault_tvl,
+        breakdown.out_amount as u128
+    );
+    assert_eq!(0, breakdown.fee_amount);
+    assert_eq!(Decimal::ZERO, breakdown.fee_pct);
+}
+
+#[tokio::test]
+async fn test_quote_breakdown_redeem() {
+    let carrot_amm = init_amm();
+    let quote_params = QuoteParams {
+        input_mint: CRT_MINT,
+        output_mint: USDT_MINT,
+        amount: 1_000,
+        swap_mode: SwapMode::ExactIn,
+    };
+
+    let breakdown = carrot_amm.quote_detailed(&quote_params).unwrap();
+    assert!(breakdown.is_redeem);
+    assert_eq!(USDT_MINT, breakdown.asset_mint);
+    assert_eq!(
+        carrot_amm
+            .get_asset_by_mint(&USDT_MINT)
+            .unwrap()
+            .oracle_price_down,
+        breakdown.asset_price
+    );
+    assert_eq!(1, breakdown.fee_amount);
+    assert_eq!(Decimal::new(1, 4), breakdown.fee_pct);
+
+    // usd earned by the shares left after the redemption fee
+    let nav = breakdown.nav;
+    assert_eq!(
+        (breakdown.in_amount - breakdown.fee_amount) as u128 * nav.vault_tvl
+            / nav.adjusted_shares_supply as u128,
+        breakdown.usd_value
+    );
+}
+
+#[tokio::test]
+async fn test_quote_breakdown_exact_out() {
+    let carrot_amm = init_amm();
+    let quote_params = QuoteParams {
+        input_mint: USDC_MINT,
+        output_mint: CRT_MINT,
+        amount: 9684056623,
+        swap_mode: SwapMode::ExactOut,
+    };
+
+    let breakdown = carrot_amm.quote_detailed(&quote_params).unwrap();
+    let quote = carrot_amm.quote(&quote_params).unwrap();
+    assert_eq!(quote.in_amount, breakdown.in_amount);
+    assert_eq!(quote.out_amount, breakdown.out_amount);
+    assert!(breakdown.out_amount >= quote_params.amount);
+}
+
+#[tokio::test]
+async fn test_quote_breakdown_paused() {
+    let mut carrot_amm = init_amm();
+    carrot_amm.vault_state.paused = true;
+
+    let err = carrot_amm
+        .quote_detailed(&QuoteParams {
+            input_mint: USDC_MINT,
+            output_mint: CRT_MINT,
+            amount: 1_000_000,
+            swap_mode: SwapMode::ExactIn,
+        })
+        .unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::VaultPaused),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
@@ -0,0 +1,105 @@
+use amm::constants::{CRT_MINT, PYUSD_MINT, USDC_MINT, USDT_MINT};
+use amm::CarrotAmm;
+use jupiter_amm_interface::{Amm, QuoteParams, SwapMode};
+use solana_sdk::pubkey::Pubkey;
+
+mod utils;
+use utils::*;
+
+// asserts that in_amount delivers at least amount and that in_amount - 1 would not
+fn assert_minimal_exact_out(
+    carrot_amm: &CarrotAmm,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+) {
+    let exact_out_quote = carrot_amm
+        .quote(&QuoteParams {
+            input_mint,
+            output_mint,
+            amount,
+            swap_mode: SwapMode::ExactOut,
+        })
+        .unwrap();
+    assert!(
+        exact_out_quote.out_amount >= amount,
+        "under-delivered {} < {} for {} -> {}",
+        exact_out_quote.out_amount,
+        amount,
+        input_mint,
+        output_mint
+    );
+
+    // the exact out quote must match an exact in quote of the same input
+    let exact_in_quote = carrot_amm
+        .quote(&QuoteParams {
+            input_mint,
+            output_mint,
+            amount: exact_out_quote.in_amount,
+            swap_mode: SwapMode::ExactIn,
+        })
+        .unwrap();
+    assert_eq!(exact_in_quote.out_amount, exact_out_quote.out_amount);
+    assert_eq!(exact_in_quote.fee_amount, exact_out_quote.fee_amount);
+
+    // one less unit of input must not be enough
+    // zero deposits are rejected, so a single unit deposit is minimal
+    if output_mint.eq(&CRT_MINT) && exact_out_quote.in_amount.eq(&1) {
+        return;
+    }
+    let smaller_quote = carrot_amm
+        .quote(&QuoteParams {
+            input_mint,
+            output_mint,
+            amount: exact_out_quote.in_amount - 1,
+            swap_mode: SwapMode::ExactIn,
+        })
+        .unwrap();
+    assert!(
+        smaller_quote.out_amount < amount,
+        "in_amount {} is not minimal for {} -> {}",
+        exact_out_quote.in_amount,
+        input_mint,
+        output_mint
+    );
+}
+
+#[tokio::test]
+async fn test_quote_issue_exact_out() {
+    let carrot_amm = init_amm();
+
+    for input_mint in [USDC_MINT, USDT_MINT, PYUSD_MINT] {
+        for amount in [1, 7, 1_000, 123_456_789, 9_684_056_623] {
+            assert_minimal_exact_out(&carrot_amm, input_mint, CRT_MINT, amount);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_quote_redeem_exact_out() {
+    let carrot_amm = init_amm();
+
+    for output_mint in [USDC_MINT, USDT_MINT, PYUSD_MINT] {
+        for amount in [1, 103, 10_000, 1_000_000] {
+            assert_minimal_exact_out(&carrot_amm, CRT_MINT, output_mint, amount);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_quote_redeem_exact_out_insufficient_liquidity() {
+    let carrot_amm = init_amm();
+
+    let quote_params = QuoteParams {
+        input_mint: CRT_MINT,
+        output_mint: USDC_MINT,
+        amount: 1_000_000_000,
+        swap_mode: SwapMode::ExactOut,
+    };
+
+    let quote_result = carrot_amm.quote(&quote_params);
+    assert!(
+        quote_result.is_err(),
+        "Expected an error, but got a successful result."
+    );
+}
@@ -0,0 +1,82 @@
+use amm::constants::{CRT_MINT, CRT_VAULT, USDC_MINT};
+use amm::errors::CarrotAmmError;
+use amm::state::Vault;
+use amm::CarrotAmm;
+use jupiter_amm_interface::{Amm, QuoteParams, SwapMode};
+use rust_decimal::Decimal;
+
+mod utils;
+use utils::*;
+
+#[tokio::test]
+async fn test_share_price() {
+    let carrot_amm = init_amm();
+    let share_price = carrot_amm.share_price().unwrap();
+
+    let shares_state = carrot_amm.shares_state.unwrap();
+    assert_eq!(shares_state.supply, share_price.shares_supply);
+    assert_eq!(shares_state.decimals, share_price.shares_decimals);
+    assert_eq!(
+        carrot_amm
+            .vault_state
+            .get_tvl(&carrot_amm.asset_state, false)
+            .unwrap(),
+        share_price.vault_tvl
+    );
+
+    // accrued fees are not minted yet and dilute the supply
+    assert!(share_price.adjusted_shares_supply >= share_price.shares_supply);
+
+    // price is tvl over supply for one whole share
+    let one_share = 10_u128.pow(share_price.shares_decimals.into());
+    assert_eq!(
+        share_price.vault_tvl * one_share / share_price.adjusted_shares_supply as u128,
+        share_price.price
+    );
+    assert_eq!(
+        Decimal::from_i128_with_scale(share_price.price as i128, 9),
+        share_price.price_decimal
+    );
+
+    // one crt is worth roughly 103 usd
+    assert!(share_price.price_decimal > Decimal::from(103));
+    assert!(share_price.price_decimal < Decimal::from(104));
+}
+
+#[tokio::test]
+async fn test_share_price_matches_redeem_quote() {
+    let mut carrot_amm = init_amm();
+    carrot_amm.vault_state.fee.redemption_fee_bps = 0;
+    let share_price = carrot_amm.share_price().unwrap();
+
+    // redeeming a thousandth of a share at the nav price yields the quoted usdc
+    let amount = 1_000_000;
+    let quote = carrot_amm
+        .quote(&QuoteParams {
+            input_mint: CRT_MINT,
+            output_mint: USDC_MINT,
+            amount,
+            swap_mode: SwapMode::ExactIn,
+        })
+        .unwrap();
+    let usdc_price = carrot_amm
+        .get_asset_by_mint(&USDC_MINT)
+        .unwrap()
+        .get_price(false);
+    let expected_usd = share_price.price_decimal / Decimal::from(1000);
+    let quoted_usd = Decimal::new(quote.out_amount as i64, 6) * Decimal::new(usdc_price, 8);
+    assert!((expected_usd - quoted_usd).abs() < Decimal::new(1, 5));
+}
+
+#[tokio::test]
+async fn test_share_price_requires_update() {
+    let account_map = load_account_map_from_file();
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    let carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+
+    let err = carrot_amm.share_price().unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::SharesStateNotInitialized),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
@@ -0,0 +1,152 @@
+use amm::constants::{CARROT_PROGRAM, CRT_MINT, USDC_MINT};
+use amm::errors::CarrotAmmError;
+use amm::instructions::{issue_data, redeem_data};
+use jupiter_amm_interface::{Amm, QuoteParams, SwapMode};
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
+
+mod utils;
+use utils::*;
+
+const USER_SOURCE: Pubkey = Pubkey::new_from_array([1; 32]);
+const USER_DESTINATION: Pubkey = Pubkey::new_from_array([2; 32]);
+const AUTHORITY: Pubkey = Pubkey::new_from_array([3; 32]);
+
+fn issue_params() -> QuoteParams {
+    QuoteParams {
+        input_mint: USDC_MINT,
+        output_mint: CRT_MINT,
+        amount: 1_000_000_000,
+        swap_mode: SwapMode::ExactIn,
+    }
+}
+
+#[tokio::test]
+async fn test_swap_with_slippage_issue() {
+    let carrot_amm = init_amm();
+
+    let swap = carrot_amm
+        .swap_with_slippage(
+            &issue_params(),
+            50,
+            USER_SOURCE,
+            USER_DESTINATION,
+            AUTHORITY,
+        )
+        .unwrap();
+
+    assert_eq!(1_000_000_000, swap.in_amount);
+    assert_eq!(9684056623, swap.out_amount);
+    assert_eq!(9684056623 * 9_950 / 10_000, swap.min_out_amount);
+    assert_eq!(USER_DESTINATION, swap.user_destination);
+
+    // same instruction the program integration test builds by hand
+    let accounts: Vec<AccountMeta> = carrot_amm
+        .carrot_swap(
+            USDC_MINT,
+            CRT_MINT,
+            USER_SOURCE,
+            USER_DESTINATION,
+            AUTHORITY,
+        )
+        .try_into()
+        .unwrap();
+    assert_eq!(CARROT_PROGRAM, swap.instruction.program_id);
+    assert_eq!(accounts, swap.instruction.accounts);
+    assert_eq!(issue_data(1_000_000_000), swap.instruction.data);
+}
+
+#[tokio::test]
+async fn test_swap_with_slippage_redeem_exact_out() {
+    let carrot_amm = init_amm();
+    let quote_params = QuoteParams {
+        input_mint: CRT_MINT,
+        output_mint: USDC_MINT,
+        amount: 103,
+        swap_mode: SwapMode::ExactOut,
+    };
+
+    let quote = carrot_amm.quote(&quote_params).unwrap();
+    let swap = carrot_amm
+        .swap_with_slippage(&quote_params, 0, USER_SOURCE, USER_DESTINATION, AUTHORITY)
+        .unwrap();
+
+    // the program takes the input amount, the requested output becomes the minimum
+    assert_eq!(redeem_data(quote.in_amount), swap.instruction.data);
+    assert_eq!(quote.out_amount, swap.min_out_amount);
+    assert!(swap.min_out_amount >= 103);
+}
+
+#[tokio::test]
+async fn test_slippage_check() {
+    let carrot_amm = init_amm();
+    let swap = carrot_amm
+        .swap_with_slippage(
+            &issue_params(),
+            100,
+            USER_SOURCE,
+            USER_DESTINATION,
+            AUTHORITY,
+        )
+        .unwrap();
+
+    swap.check(5, 5 + swap.min_out_amount).unwrap();
+    swap.check(5, 5 + swap.out_amount).unwrap();
+
+    let err = swap.check(5, 4 + swap.min_out_amount).unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::SlippageExceeded {
+            min_out_amount: swap.min_out_amount,
+            out_amount: swap.min_out_amount - 1,
+        }),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+
+    // a shrinking balance never passes
+    assert!(swap.check(5, 0).is_err());
+}
+
+#[tokio::test]
+async fn test_swap_with_slippage_errors() {
+    let mut carrot_amm = init_amm();
+
+    let err = carrot_amm
+        .swap_with_slippage(
+            &issue_params(),
+            10_001,
+            USER_SOURCE,
+            USER_DESTINATION,
+            AUTHORITY,
+        )
+        .unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::InvalidSlippageBps),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+
+    // full slippage accepts any output
+    let swap = carrot_amm
+        .swap_with_slippage(
+            &issue_params(),
+            10_000,
+            USER_SOURCE,
+            USER_DESTINATION,
+            AUTHORITY,
+        )
+        .unwrap();
+    assert_eq!(0, swap.min_out_amount);
+
+    carrot_amm.vault_state.paused = true;
+    let err = carrot_amm
+        .swap_with_slippage(
+            &issue_params(),
+            50,
+            USER_SOURCE,
+            USER_DESTINATION,
+            AUTHORITY,
+        )
+        .unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::VaultPaused),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
@@ -0,0 +1,121 @@
+use amm::constants::{
+    CARROT_LOG_PROGRAM, CRT_MINT, CRT_VAULT, PYUSD_MINT, PYUSD_ORACLE, PYUSD_VAULT_ATA,
+    TOKEN_22_PROGRAM, TOKEN_PROGRAM, USDC_MINT, USDC_ORACLE, USDC_VAULT_ATA, USDT_ORACLE,
+    USDT_VAULT_ATA,
+};
+use amm::CarrotSwap;
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, system_program::ID as SystemProgramId};
+
+mod utils;
+use utils::*;
+
+fn expected_remaining_accounts() -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(USDC_ORACLE, false),
+        AccountMeta::new_readonly(USDT_ORACLE, false),
+        AccountMeta::new_readonly(PYUSD_ORACLE, false),
+        AccountMeta::new_readonly(USDC_VAULT_ATA, false),
+        AccountMeta::new_readonly(USDT_VAULT_ATA, false),
+        AccountMeta::new_readonly(PYUSD_VAULT_ATA, false),
+    ]
+}
+
+#[tokio::test]
+async fn test_issue_account_metas() {
+    let carrot_amm = init_amm();
+
+    let user_source = Pubkey::new_unique();
+    let user_destination = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+
+    let account_metas: Vec<AccountMeta> = CarrotSwap {
+        vault: CRT_VAULT,
+        shares_mint: CRT_MINT,
+        source_mint: USDC_MINT,
+        destination_mint: CRT_MINT,
+        user_source,
+        user_destination,
+        user_transfer_authority: authority,
+        assets: carrot_amm.asset_state.clone(),
+    }
+    .try_into()
+    .unwrap();
+
+    let mut expected = vec![
+        AccountMeta::new(CRT_VAULT, false),
+        AccountMeta::new(CRT_MINT, false),
+        AccountMeta::new(user_destination, false),
+        AccountMeta::new_readonly(USDC_MINT, false),
+        AccountMeta::new(USDC_VAULT_ATA, false),
+        AccountMeta::new(user_source, false),
+        AccountMeta::new_readonly(authority, true),
+        AccountMeta::new_readonly(SystemProgramId, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+        AccountMeta::new_readonly(TOKEN_22_PROGRAM, false),
+        AccountMeta::new_readonly(CARROT_LOG_PROGRAM, false),
+    ];
+    expected.extend(expected_remaining_accounts());
+
+    assert_eq!(expected, account_metas);
+}
+
+#[tokio::test]
+async fn test_redeem_account_metas() {
+    let carrot_amm = init_amm();
+
+    let user_source = Pubkey::new_unique();
+    let user_destination = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+
+    let account_metas: Vec<AccountMeta> = CarrotSwap {
+        vault: CRT_VAULT,
+        shares_mint: CRT_MINT,
+        source_mint: CRT_MINT,
+        destination_mint: PYUSD_MINT,
+        user_source,
+        user_destination,
+        user_transfer_authority: authority,
+        assets: carrot_amm.asset_state.clone(),
+    }
+    .try_into()
+    .unwrap();
+
+    let mut expected = vec![
+        AccountMeta::new(CRT_VAULT, false),
+        AccountMeta::new(CRT_MINT, false),
+        AccountMeta::new(user_source, false),
+        AccountMeta::new_readonly(PYUSD_MINT, false),
+        AccountMeta::new(PYUSD_VAULT_ATA, false),
+        AccountMeta::new(user_destination, false),
+        AccountMeta::new_readonly(authority, true),
+        AccountMeta::new_readonly(SystemProgramId, false),
+        AccountMeta::new_readonly(TOKEN_22_PROGRAM, false),
+        AccountMeta::new_readonly(TOKEN_22_PROGRAM, false),
+        AccountMeta::new_readonly(CARROT_LOG_PROGRAM, false),
+    ];
+    expected.extend(expected_remaining_accounts());
+
+    assert_eq!(expected, account_metas);
+}
+
+#[tokio::test]
+async fn test_account_metas_unknown_asset() {
+    let carrot_amm = init_amm();
+
+    let result: anyhow::Result<Vec<AccountMeta>> = CarrotSwap {
+        vault: CRT_VAULT,
+        shares_mint: CRT_MINT,
+        source_mint: Pubkey::new_unique(),
+        destination_mint: CRT_MINT,
+        user_source: Pubkey::new_unique(),
+        user_destination: Pubkey::new_unique(),
+        user_transfer_authority: Pubkey::new_unique(),
+        assets: carrot_amm.asset_state.clone(),
+    }
+    .try_into();
+
+    assert!(
+        result.is_err(),
+        "Expected an error, but got a successful result."
+    );
+}
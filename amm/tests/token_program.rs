@@ -0,0 +1,85 @@
+use amm::constants::{
+    CRT_MINT, PYUSD_MINT, PYUSD_VAULT_ATA, TOKEN_22_PROGRAM, TOKEN_PROGRAM, USDC_MINT, USDT_MINT,
+    USDT_VAULT_ATA,
+};
+use amm::errors::CarrotAmmError;
+use amm::CarrotAmm;
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
+
+mod utils;
+use utils::*;
+
+// position of the asset token program in the swap accounts
+const ASSET_TOKEN_PROGRAM_INDEX: usize = 8;
+
+fn issue_account_metas(carrot_amm: &CarrotAmm, asset_mint: Pubkey) -> Vec<AccountMeta> {
+    carrot_amm
+        .carrot_swap(
+            asset_mint,
+            CRT_MINT,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        )
+        .try_into()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_token_program_from_account_owner() {
+    let carrot_amm = try_init_amm_with_accounts(&load_account_map_from_file()).unwrap();
+
+    for (mint, token_program) in [
+        (USDC_MINT, TOKEN_PROGRAM),
+        (USDT_MINT, TOKEN_PROGRAM),
+        (PYUSD_MINT, TOKEN_22_PROGRAM),
+    ] {
+        let asset = carrot_amm.get_asset_by_mint(&mint).unwrap();
+        assert_eq!(token_program, asset.mint_token_program);
+        assert_eq!(token_program, asset.ata_token_program);
+
+        let account_metas = issue_account_metas(&carrot_amm, mint);
+        assert_eq!(
+            AccountMeta::new_readonly(token_program, false),
+            account_metas[ASSET_TOKEN_PROGRAM_INDEX]
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_token_22_asset_without_extensions() {
+    let mut account_map = load_account_map_from_file();
+
+    // a plain spl-token layout is a valid token-2022 account without extensions
+    account_map.get_mut(&USDT_MINT).unwrap().owner = TOKEN_22_PROGRAM;
+    account_map.get_mut(&USDT_VAULT_ATA).unwrap().owner = TOKEN_22_PROGRAM;
+
+    let carrot_amm = try_init_amm_with_accounts(&account_map).unwrap();
+    let asset = carrot_amm.get_asset_by_mint(&USDT_MINT).unwrap();
+    assert_eq!(TOKEN_22_PROGRAM, asset.mint_token_program);
+    assert_eq!(TOKEN_22_PROGRAM, asset.ata_token_program);
+
+    let account_metas = issue_account_metas(&carrot_amm, USDT_MINT);
+    assert_eq!(
+        AccountMeta::new_readonly(TOKEN_22_PROGRAM, false),
+        account_metas[ASSET_TOKEN_PROGRAM_INDEX]
+    );
+}
+
+#[tokio::test]
+async fn test_token_program_mismatch() {
+    let mut account_map = load_account_map_from_file();
+
+    // the pyusd mint is token-2022 but its ata now claims spl-token
+    account_map.get_mut(&PYUSD_VAULT_ATA).unwrap().owner = TOKEN_PROGRAM;
+
+    let err = try_init_amm_with_accounts(&account_map).err().unwrap();
+    assert_eq!(
+        Some(&CarrotAmmError::TokenProgramMismatch {
+            account: PYUSD_VAULT_ATA,
+            owner: TOKEN_PROGRAM,
+            expected: TOKEN_22_PROGRAM,
+        }),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}
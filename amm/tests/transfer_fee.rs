@@ -0,0 +1,248 @@
+use amm::constants::{
+    CRT_MINT, CRT_VAULT, PYUSD_MINT, TOKEN_22_PROGRAM, USDC_MINT, USDC_VAULT_ATA,
+};
+use amm::errors::CarrotAmmError;
+use amm::{state::Vault, CarrotAmm};
+use jupiter_amm_interface::{AccountMap, Amm, ClockRef, QuoteParams, SwapMode};
+use solana_sdk::{program_option::COption, pubkey::Pubkey};
+use spl_token_2022::extension::{
+    interest_bearing_mint::InterestBearingConfig, BaseStateWithExtensionsMut, ExtensionType,
+    StateWithExtensionsMut,
+};
+use spl_token_2022::state::Mint;
+use std::sync::atomic::Ordering;
+
+mod utils;
+use utils::*;
+
+fn with_pyusd_transfer_fee(older_bps: u16, newer_bps: u16, newer_epoch: u64) -> AccountMap {
+    let mut account_map = load_account_map_from_file();
+    set_pyusd_transfer_fee(&mut account_map, older_bps, newer_bps, newer_epoch);
+    account_map
+}
+
+fn quote_params(
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+    swap_mode: SwapMode,
+) -> QuoteParams {
+    QuoteParams {
+        input_mint,
+        output_mint,
+        amount,
+        swap_mode,
+    }
+}
+
+#[tokio::test]
+async fn test_fixture_mint_extensions() {
+    let account_map = load_account_map_from_file();
+    let carrot_amm = init_amm_with_accounts(&account_map);
+
+    // mints are refreshed with the rest of the vault accounts
+    let accounts = carrot_amm.get_accounts_to_update();
+    for asset in carrot_amm.vault_state.assets.iter() {
+        assert!(accounts.contains(&asset.mint));
+    }
+
+    // pyusd has a zero transfer fee configured, the spl token mints have no extensions
+    let pyusd = carrot_amm.get_asset_by_mint(&PYUSD_MINT).unwrap();
+    let transfer_fee = pyusd.transfer_fee.unwrap();
+    assert_eq!(0, u16::from(transfer_fee.transfer_fee_basis_points));
+    assert!(pyusd.interest_bearing.is_none());
+    let usdc = carrot_amm.get_asset_by_mint(&USDC_MINT).unwrap();
+    assert!(usdc.transfer_fee.is_none());
+    assert!(usdc.interest_bearing.is_none());
+
+    let breakdown = carrot_amm
+        .quote_detailed(&quote_params(
+            PYUSD_MINT,
+            CRT_MINT,
+            1_000_000,
+            SwapMode::ExactIn,
+        ))
+        .unwrap();
+    assert_eq!(0, breakdown.transfer_fee_amount);
+}
+
+#[tokio::test]
+async fn test_issue_ignores_transfer_fee() {
+    let fee_free = init_amm_with_accounts(&load_account_map_from_file());
+    let carrot_amm = init_amm_with_accounts(&with_pyusd_transfer_fee(100, 100, 0));
+
+    let breakdown = carrot_amm
+        .quote_detailed(&quote_params(
+            PYUSD_MINT,
+            CRT_MINT,
+            1_000_000,
+            SwapMode::ExactIn,
+        ))
+        .unwrap();
+    assert_eq!(1_000_000, breakdown.in_amount);
+    assert_eq!(10_000, breakdown.transfer_fee_amount);
+
+    // the program prices shares on the deposit, the vault absorbs the fee
+    let gross_quote = fee_free
+        .quote(&quote_params(
+            PYUSD_MINT,
+            CRT_MINT,
+            1_000_000,
+            SwapMode::ExactIn,
+        ))
+        .unwrap();
+    assert_eq!(gross_quote.out_amount, breakdown.out_amount);
+}
+
+#[tokio::test]
+async fn test_redeem_nets_transfer_fee() {
+    let fee_free = init_amm_with_accounts(&load_account_map_from_file());
+    let carrot_amm = init_amm_with_accounts(&with_pyusd_transfer_fee(100, 100, 0));
+
+    let params = quote_params(CRT_MINT, PYUSD_MINT, 10_000_000, SwapMode::ExactIn);
+    let gross = fee_free.quote(&params).unwrap().out_amount;
+    let breakdown = carrot_amm.quote_detailed(&params).unwrap();
+
+    // transfer fees round up
+    let transfer_fee = (gross * 100).div_ceil(10_000);
+    assert_eq!(transfer_fee, breakdown.transfer_fee_amount);
+    assert_eq!(gross - transfer_fee, breakdown.out_amount);
+}
+
+#[tokio::test]
+async fn test_exact_out_grosses_up_transfer_fee() {
+    let carrot_amm = init_amm_with_accounts(&with_pyusd_transfer_fee(250, 250, 0));
+
+    for (input_mint, output_mint, amount) in [
+        (PYUSD_MINT, CRT_MINT, 5_000_000_000),
+        (CRT_MINT, PYUSD_MINT, 1_000_000),
+    ] {
+        let quote = carrot_amm
+            .quote(&quote_params(
+                input_mint,
+                output_mint,
+                amount,
+                SwapMode::ExactOut,
+            ))
+            .unwrap();
+        assert!(quote.out_amount >= amount);
+
+        // one unit less under-delivers
+        let under = carrot_amm
+            .quote(&quote_params(
+                input_mint,
+                output_mint,
+                quote.in_amount - 1,
+                SwapMode::ExactIn,
+            ))
+            .unwrap();
+        assert!(under.out_amount < amount);
+    }
+}
+
+#[tokio::test]
+async fn test_transfer_fee_follows_epoch() {
+    let account_map = with_pyusd_transfer_fee(100, 200, 700);
+    let params = quote_params(PYUSD_MINT, CRT_MINT, 1_000_000, SwapMode::ExactIn);
+
+    for (epoch, transfer_fee_amount) in [(699, 10_000), (700, 20_000)] {
+        let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+        let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+        let clock_ref = ClockRef::default();
+        clock_ref.epoch.store(epoch, Ordering::Relaxed);
+        carrot_amm.clock_ref = Some(clock_ref);
+        carrot_amm.update(&account_map).unwrap();
+
+        let breakdown = carrot_amm.quote_detailed(&params).unwrap();
+        assert_eq!(transfer_fee_amount, breakdown.transfer_fee_amount);
+    }
+}
+
+#[tokio::test]
+async fn test_transfer_fee_without_epoch() {
+    let vault_state =
+        Vault::load(&load_account_map_from_file().get(&CRT_VAULT).unwrap().data).unwrap();
+
+    // the fee changes at epoch 700, so it is unknown without a clock or with an unset epoch
+    let account_map = with_pyusd_transfer_fee(100, 200, 700);
+    for clock_ref in [None, Some(ClockRef::default())] {
+        let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state.clone(), u64::MAX);
+        carrot_amm.clock_ref = clock_ref;
+        let err = carrot_amm.update(&account_map).unwrap_err();
+        assert_eq!(
+            Some(&CarrotAmmError::UnknownEpoch(PYUSD_MINT)),
+            err.downcast_ref::<CarrotAmmError>()
+        );
+    }
+
+    // the same fee on both sides of the change does not need the epoch
+    let carrot_amm = init_amm_with_accounts(&with_pyusd_transfer_fee(100, 100, 700));
+    let breakdown = carrot_amm
+        .quote_detailed(&quote_params(
+            PYUSD_MINT,
+            CRT_MINT,
+            1_000_000,
+            SwapMode::ExactIn,
+        ))
+        .unwrap();
+    assert_eq!(10_000, breakdown.transfer_fee_amount);
+}
+
+#[tokio::test]
+async fn test_interest_bearing_mint() {
+    let mut account_map = load_account_map_from_file();
+    let fee_free = init_amm_with_accounts(&account_map);
+
+    // re-create usdc as a token-2022 mint with an interest bearing config
+    let usdc = account_map.get_mut(&USDC_MINT).unwrap();
+    let decimals = usdc.data[44];
+    let len =
+        ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::InterestBearingConfig])
+            .unwrap();
+    let mut data = vec![0; len];
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    let config = mint.init_extension::<InterestBearingConfig>(true).unwrap();
+    config.current_rate = 500.into();
+    mint.base = Mint {
+        mint_authority: COption::None,
+        supply: 1_000_000_000,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    mint.pack_base();
+    mint.init_account_type().unwrap();
+    usdc.data = data;
+    usdc.owner = TOKEN_22_PROGRAM;
+    account_map.get_mut(&USDC_VAULT_ATA).unwrap().owner = TOKEN_22_PROGRAM;
+
+    let carrot_amm = init_amm_with_accounts(&account_map);
+    let asset = carrot_amm.get_asset_by_mint(&USDC_MINT).unwrap();
+    assert_eq!(500, i16::from(asset.interest_bearing.unwrap().current_rate));
+    assert!(asset.transfer_fee.is_none());
+
+    // raw amounts are unaffected by interest
+    let params = quote_params(USDC_MINT, CRT_MINT, 1_000_000_000, SwapMode::ExactIn);
+    assert_eq!(
+        fee_free.quote(&params).unwrap().out_amount,
+        carrot_amm.quote(&params).unwrap().out_amount
+    );
+}
+
+#[tokio::test]
+async fn test_missing_asset_mint() {
+    let mut account_map = load_account_map_from_file();
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+
+    // an asset mint owned by another program is rejected
+    account_map.get_mut(&PYUSD_MINT).unwrap().owner = CRT_VAULT;
+    let err = carrot_amm.update(&account_map).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CarrotAmmError>(),
+        Some(CarrotAmmError::InvalidAccountOwner { .. })
+    ));
+
+    account_map.remove(&PYUSD_MINT);
+    assert!(carrot_amm.update(&account_map).is_err());
+}
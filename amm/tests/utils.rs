@@ -1,16 +1,273 @@
+#![allow(dead_code)]
+
+use amm::constants::{
+    CARROT_LOG_PROGRAM, CARROT_PROGRAM, CRT_MINT, CRT_VAULT, PYUSD_MINT, USDC_MINT, USDT_MINT,
+};
+use amm::{state::Vault, CarrotAmm};
 use base64::{engine::general_purpose::STANDARD, Engine};
-use solana_program_test::BanksClient;
+use solana_program_test::{BanksClient, ProgramTest};
 use std::collections::HashMap;
 use std::str::FromStr;
 
-use jupiter_amm_interface::AccountMap;
+use jupiter_amm_interface::{AccountMap, Amm};
 use serde_json::Value;
 use solana_sdk::account::Account;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_pack::Pack;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use spl_token::{id as token_program_id, state::Account as TokenAccount};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeConfig, BaseStateWithExtensionsMut, StateWithExtensions,
+        StateWithExtensionsMut,
+    },
+    id as token_2022_program_id,
+    state::{Account as Token2022Account, Mint as Token2022Mint},
+};
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
+// mints used by the vault fixture and the token program that owns them
+pub fn fixture_input_mints() -> Vec<(Pubkey, Pubkey)> {
+    vec![
+        (USDC_MINT, token_program_id()),
+        (USDT_MINT, token_program_id()),
+        (PYUSD_MINT, token_2022_program_id()),
+    ]
+}
+
+// amm for the fixture vault, updated from the fixture accounts
+pub fn init_amm() -> CarrotAmm {
+    init_amm_with_accounts(&load_account_map_from_file())
+}
+
+// amm for the fixture vault, updated from the given accounts
+pub fn init_amm_with_accounts(account_map: &AccountMap) -> CarrotAmm {
+    try_init_amm_with_accounts(account_map).unwrap()
+}
+
+// same as init_amm_with_accounts but returns the update error
+pub fn try_init_amm_with_accounts(account_map: &AccountMap) -> anyhow::Result<CarrotAmm> {
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+    carrot_amm.update(account_map)?;
+    Ok(carrot_amm)
+}
+
+// overrides the cached usdc prices used by quotes
+pub fn with_usdc_price(mut carrot_amm: CarrotAmm, price: i64, expo: i32) -> CarrotAmm {
+    let asset = carrot_amm
+        .asset_state
+        .iter_mut()
+        .find(|a| a.mint.eq(&USDC_MINT))
+        .unwrap();
+    asset.oracle_price_up = price;
+    asset.oracle_price_down = price;
+    asset.oracle_price_expo = expo;
+    carrot_amm
+}
+
+// sets the older fee, used before newer_epoch, and the newer fee of the pyusd mint
+pub fn set_pyusd_transfer_fee(
+    account_map: &mut AccountMap,
+    older_bps: u16,
+    newer_bps: u16,
+    newer_epoch: u64,
+) {
+    let data = &mut account_map.get_mut(&PYUSD_MINT).unwrap().data;
+    let mut mint = StateWithExtensionsMut::<Token2022Mint>::unpack(data).unwrap();
+    let config = mint.get_extension_mut::<TransferFeeConfig>().unwrap();
+    config.older_transfer_fee.epoch = 0.into();
+    config.older_transfer_fee.transfer_fee_basis_points = older_bps.into();
+    config.older_transfer_fee.maximum_fee = u64::MAX.into();
+    config.newer_transfer_fee.epoch = newer_epoch.into();
+    config.newer_transfer_fee.transfer_fee_basis_points = newer_bps.into();
+    config.newer_transfer_fee.maximum_fee = u64::MAX.into();
+}
+
+pub struct TestEnv {
+    pub banks_client: BanksClient,
+    pub payer: Keypair,
+    pub recent_blockhash: Hash,
+    pub account_map: AccountMap,
+    pub payer_shares_ata: Pubkey,
+    pub payer_input_mint_ata: Pubkey,
+}
+
+// starts a program test with the carrot programs and fixture accounts loaded
+// and funds the payer with `payer_input_mint_to` of input_mint and an empty shares account
+pub async fn setup_test_env(
+    input_mint: Pubkey,
+    token_program: Pubkey,
+    payer_input_mint_to: u64,
+) -> TestEnv {
+    setup_test_env_with_accounts(
+        load_account_map_from_file(),
+        input_mint,
+        token_program,
+        payer_input_mint_to,
+    )
+    .await
+}
+
+// same as setup_test_env but loads the given accounts instead of the fixtures
+pub async fn setup_test_env_with_accounts(
+    account_map: AccountMap,
+    input_mint: Pubkey,
+    token_program: Pubkey,
+    payer_input_mint_to: u64,
+) -> TestEnv {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+
+    // add carrot programs
+    program_test.add_program("carrot", CARROT_PROGRAM, None);
+    program_test.add_program("carrot-log", CARROT_LOG_PROGRAM, None);
+
+    // add all accounts to test harness
+    for (address, account) in account_map.iter() {
+        program_test.add_account(*address, account.clone());
+    }
+
+    let rent = Rent::default();
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // use only for testing
+    let mint_authority = Keypair::from_bytes(&[
+        6, 171, 218, 28, 81, 132, 195, 119, 106, 186, 21, 46, 6, 145, 196, 80, 151, 235, 245, 249,
+        240, 102, 193, 29, 49, 156, 126, 163, 100, 6, 170, 23, 145, 253, 146, 149, 201, 100, 48,
+        121, 249, 162, 172, 54, 190, 206, 106, 122, 68, 188, 49, 13, 252, 67, 233, 155, 72, 58, 62,
+        174, 239, 185, 65, 165,
+    ])
+    .unwrap();
+
+    let payer_shares_ata = Keypair::new();
+    let payer_input_mint_ata = Keypair::new();
+
+    let (account_len, init_account_ix, mint_to_ix) = if token_program.eq(&token_2022_program_id()) {
+        let init_account_ix: Instruction = spl_token_2022::instruction::initialize_account3(
+            &token_program,
+            &payer_input_mint_ata.pubkey(),
+            &input_mint,
+            &payer.pubkey(),
+        )
+        .unwrap();
+        let mint_to_ix = spl_token_2022::instruction::mint_to(
+            &token_program,
+            &input_mint,
+            &payer_input_mint_ata.pubkey(),
+            &mint_authority.pubkey(),
+            &[&mint_authority.pubkey()],
+            payer_input_mint_to,
+        )
+        .unwrap();
+
+        // init the correct len because pyusd has all extensions enabled so it needs a larger token account
+        let mut account_len = Token2022Account::LEN;
+        if input_mint.eq(&PYUSD_MINT) {
+            account_len = 187;
+        };
+
+        (account_len, init_account_ix, mint_to_ix)
+    } else {
+        let init_account_ix = spl_token::instruction::initialize_account3(
+            &token_program,
+            &payer_input_mint_ata.pubkey(),
+            &input_mint,
+            &payer.pubkey(),
+        )
+        .unwrap();
+        let mint_to_ix = spl_token::instruction::mint_to(
+            &token_program,
+            &input_mint,
+            &payer_input_mint_ata.pubkey(),
+            &mint_authority.pubkey(),
+            &[&mint_authority.pubkey()],
+            payer_input_mint_to,
+        )
+        .unwrap();
+        (TokenAccount::LEN, init_account_ix, mint_to_ix)
+    };
+
+    let setup_payer_tx = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &payer_shares_ata.pubkey(),
+                rent.minimum_balance(Token2022Account::LEN),
+                Token2022Account::LEN as u64,
+                &token_2022_program_id(),
+            ),
+            spl_token_2022::instruction::initialize_account3(
+                &token_2022_program_id(),
+                &payer_shares_ata.pubkey(),
+                &CRT_MINT,
+                &payer.pubkey(),
+            )
+            .unwrap(),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &payer_input_mint_ata.pubkey(),
+                rent.minimum_balance(account_len),
+                account_len as u64,
+                &token_program,
+            ),
+            init_account_ix,
+            mint_to_ix,
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &mint_authority,
+            &payer_shares_ata,
+            &payer_input_mint_ata,
+        ],
+        recent_blockhash,
+    );
+
+    banks_client
+        .process_transaction_with_metadata(setup_payer_tx)
+        .await
+        .unwrap()
+        .result
+        .unwrap();
+
+    TestEnv {
+        banks_client,
+        payer,
+        recent_blockhash,
+        account_map,
+        payer_shares_ata: payer_shares_ata.pubkey(),
+        payer_input_mint_ata: payer_input_mint_ata.pubkey(),
+    }
+}
+
+// reads the amount of a spl-token or spl-token-2022 account
+pub async fn get_token_balance(banks_client: &mut BanksClient, address: Pubkey) -> u64 {
+    let account = banks_client.get_account(address).await.unwrap().unwrap();
+
+    StateWithExtensions::<Token2022Account>::unpack(&account.data)
+        .unwrap()
+        .base
+        .amount
+}
+
+// re-fetches every account of the map from the bank
+pub async fn refresh_account_map(
+    banks_client: &mut BanksClient,
+    account_map: &AccountMap,
+) -> AccountMap {
+    let addresses: Vec<Pubkey> = account_map.keys().cloned().collect();
+    load_account_map_from_bank(banks_client, addresses.as_slice()).await
+}
+
 pub fn load_account_map_from_file() -> AccountMap {
     let paths = [
         "tests/fixtures/vault.json",
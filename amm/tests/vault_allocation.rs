@@ -0,0 +1,68 @@
+use amm::constants::{PYUSD_MINT, USDC_MINT, USDT_MINT};
+use rust_decimal::Decimal;
+
+mod utils;
+use utils::*;
+
+#[tokio::test]
+async fn test_vault_allocation() {
+    let carrot_amm = init_amm();
+
+    let vault_state = &carrot_amm.vault_state;
+    let allocation = vault_state.allocation(&carrot_amm.asset_state).unwrap();
+
+    // totals match the tvl used by quotes
+    assert_eq!(
+        vault_state.get_tvl(&carrot_amm.asset_state, false).unwrap(),
+        allocation.tvl
+    );
+    assert_eq!(
+        allocation.tvl,
+        allocation
+            .assets
+            .iter()
+            .map(|a| a.total_balance_usd)
+            .sum::<u128>()
+    );
+
+    // reserves come from the vault atas
+    let reserves: Vec<_> = allocation
+        .assets
+        .iter()
+        .map(|a| (a.mint, a.reserve_balance))
+        .collect();
+    assert_eq!(
+        vec![
+            (USDC_MINT, 47639006),
+            (USDT_MINT, 49854684),
+            (PYUSD_MINT, 4972405)
+        ],
+        reserves
+    );
+
+    // every strategy record shows up once with its earnings
+    assert_eq!(
+        vault_state.strategies.len(),
+        allocation.strategies().count()
+    );
+    for record in vault_state.strategies.iter() {
+        let strat = allocation.get_strategy(record.strategy_id).unwrap();
+        assert_eq!(record.asset_id, strat.asset_id);
+        assert_eq!(record.balance, strat.balance);
+        assert_eq!(record.net_earnings, strat.net_earnings);
+    }
+
+    // shares of tvl add up to one
+    let total_share: Decimal = allocation.assets.iter().map(|a| a.tvl_share).sum();
+    assert!((Decimal::ONE - total_share).abs() < Decimal::new(1, 20));
+    for asset in allocation.assets.iter() {
+        let parts: Decimal = asset.reserve_tvl_share
+            + asset
+                .strategies
+                .iter()
+                .map(|s| s.tvl_share)
+                .sum::<Decimal>();
+        assert!((asset.tvl_share - parts).abs() < Decimal::new(1, 20));
+    }
+    assert!(allocation.get_asset(u16::MAX).is_none());
+}
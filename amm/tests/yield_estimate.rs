@@ -0,0 +1,133 @@
+use amm::constants::CRT_VAULT;
+use amm::errors::CarrotAmmError;
+use amm::snapshot::{estimate_yield, VaultSnapshot};
+use amm::{state::Vault, CarrotAmm};
+use jupiter_amm_interface::{Amm, ClockRef};
+use rust_decimal::Decimal;
+use std::sync::atomic::Ordering;
+
+mod utils;
+use utils::*;
+
+const SECONDS_IN_YEAR: i64 = 31557600;
+
+fn snapshot_at(timestamp: i64, nav: u128) -> VaultSnapshot {
+    VaultSnapshot {
+        slot: timestamp as u64 * 2,
+        timestamp,
+        vault_tvl: nav * 1_000,
+        adjusted_shares_supply: 1_000_000_000_000,
+        nav,
+    }
+}
+
+fn assert_close(expected: Decimal, actual: Decimal) {
+    assert!(
+        (expected - actual).abs() < Decimal::new(1, 9),
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[tokio::test]
+async fn test_snapshot_from_amm() {
+    let account_map = load_account_map_from_file();
+    let vault_state = Vault::load(&account_map.get(&CRT_VAULT).unwrap().data).unwrap();
+    let mut carrot_amm = CarrotAmm::new(CRT_VAULT, vault_state, u64::MAX);
+
+    let clock_ref = ClockRef::default();
+    clock_ref.slot.store(295_000_000, Ordering::Relaxed);
+    clock_ref
+        .unix_timestamp
+        .store(1728924130, Ordering::Relaxed);
+    carrot_amm.clock_ref = Some(clock_ref);
+    carrot_amm.update(&account_map).unwrap();
+
+    let share_price = carrot_amm.share_price().unwrap();
+    let snapshot = carrot_amm.snapshot().unwrap();
+    assert_eq!(295_000_000, snapshot.slot);
+    assert_eq!(1728924130, snapshot.timestamp);
+    assert_eq!(share_price.vault_tvl, snapshot.vault_tvl);
+    assert_eq!(
+        share_price.adjusted_shares_supply,
+        snapshot.adjusted_shares_supply
+    );
+    assert_eq!(share_price.price, snapshot.nav);
+}
+
+#[tokio::test]
+async fn test_estimate_yield() {
+    let half_year = SECONDS_IN_YEAR / 2;
+    let snapshots = [
+        snapshot_at(half_year, 1_010_000_000),
+        snapshot_at(0, 1_000_000_000),
+        snapshot_at(half_year / 2, 1_004_000_000),
+    ];
+
+    // 1% over half a year
+    let estimate = estimate_yield(&snapshots, half_year).unwrap();
+    assert_eq!(snapshots[1], estimate.start);
+    assert_eq!(snapshots[0], estimate.end);
+    assert_eq!(half_year, estimate.elapsed_seconds);
+    assert_close(Decimal::new(1, 2), estimate.period_return);
+    assert_close(Decimal::new(2, 2), estimate.apr);
+    assert_close(Decimal::new(201, 4), estimate.apy.unwrap());
+
+    // a shorter window starts at the most recent snapshot old enough
+    let estimate = estimate_yield(&snapshots, half_year / 2).unwrap();
+    assert_eq!(snapshots[2], estimate.start);
+    assert_eq!(half_year - half_year / 2, estimate.elapsed_seconds);
+
+    // losses are negative
+    let estimate = snapshot_at(SECONDS_IN_YEAR, 990_000_000)
+        .yield_since(&snapshot_at(0, 1_000_000_000))
+        .unwrap();
+    assert_close(Decimal::new(-1, 2), estimate.apr);
+    assert_close(Decimal::new(-1, 2), estimate.apy.unwrap());
+}
+
+#[tokio::test]
+async fn test_estimate_yield_short_window() {
+    // 1% over five seconds compounds far past what fits, apr is still defined
+    let estimate = snapshot_at(5, 1_010_000_000)
+        .yield_since(&snapshot_at(0, 1_000_000_000))
+        .unwrap();
+    assert_eq!(5, estimate.elapsed_seconds);
+    assert_close(Decimal::new(1, 2), estimate.period_return);
+    assert_close(Decimal::new(631_152, 1), estimate.apr);
+    assert_eq!(None, estimate.apy);
+
+    // a small move over a few seconds still compounds
+    let estimate = snapshot_at(3, 1_000_000_001)
+        .yield_since(&snapshot_at(0, 1_000_000_000))
+        .unwrap();
+    assert_close(Decimal::new(105_192, 7), estimate.apr);
+    assert!(estimate.apy.unwrap() > estimate.apr);
+}
+
+#[tokio::test]
+async fn test_estimate_yield_insufficient_snapshots() {
+    let cases: [&[VaultSnapshot]; 3] = [
+        &[],
+        &[snapshot_at(100, 1_000_000_000)],
+        &[
+            snapshot_at(100, 1_000_000_000),
+            snapshot_at(150, 1_000_000_000),
+        ],
+    ];
+    for snapshots in cases {
+        let err = estimate_yield(snapshots, 60).unwrap_err();
+        assert_eq!(
+            Some(&CarrotAmmError::InsufficientSnapshots),
+            err.downcast_ref::<CarrotAmmError>()
+        );
+    }
+
+    // a zero nav start has no defined return
+    let err = snapshot_at(100, 1)
+        .yield_since(&snapshot_at(0, 0))
+        .unwrap_err();
+    assert_eq!(
+        Some(&CarrotAmmError::InsufficientSnapshots),
+        err.downcast_ref::<CarrotAmmError>()
+    );
+}